@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// feeds are keyed by "{category}/{name}", e.g. "class/4AHIT"
+#[derive(Default)]
+pub struct IcsServer {
+    feeds: Arc<Mutex<HashMap<String, String>>>,
+    running: Arc<Mutex<Running>>,
+}
+
+// the port and the stop flag of the serving thread
+type Running = Option<(u16, Arc<AtomicBool>)>;
+
+// accept errors like a peer resetting the connection pass, this many in a row mean the listener is broken
+const MAX_ACCEPT_ERRORS: u32 = 10;
+const MAX_HEADER_LINES: usize = 100;
+
+#[tauri::command]
+pub fn configure_ics_server(
+    state: tauri::State<'_, IcsServer>,
+    enabled: bool,
+    port: u16,
+) -> Result<(), String> {
+    let mut running = state.running.lock().map_err(|e| e.to_string())?;
+
+    if let Some((current_port, stop)) = running.as_ref() {
+        if enabled && *current_port == port {
+            return Ok(());
+        }
+        stop.store(true, Ordering::Relaxed);
+        *running = None;
+    }
+
+    if !enabled {
+        return Ok(());
+    }
+
+    // only reachable from this machine
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| e.to_string())?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let feeds = state.feeds.clone();
+    let thread_stop = stop.clone();
+    let thread_running = state.running.clone();
+    thread::spawn(move || {
+        serve(listener, feeds, &thread_stop);
+        // a server that gave up must not be reported as running, unless it has been replaced already
+        if let Ok(mut running) = thread_running.lock() {
+            if running.as_ref().is_some_and(|(_, s)| Arc::ptr_eq(s, &thread_stop)) {
+                *running = None;
+            }
+        }
+    });
+
+    *running = Some((port, stop));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn publish_ics_feeds(
+    state: tauri::State<'_, IcsServer>,
+    feeds: HashMap<String, String>,
+) -> Result<(), String> {
+    let mut current = state.feeds.lock().map_err(|e| e.to_string())?;
+    *current = feeds;
+    Ok(())
+}
+
+// the listener is non-blocking so the thread notices when it gets stopped
+fn serve(listener: TcpListener, feeds: Arc<Mutex<HashMap<String, String>>>, stop: &AtomicBool) {
+    let mut errors = 0;
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                errors = 0;
+                if let Err(e) = handle(stream, &feeds) {
+                    log::warn!("Failed to answer a feed request: {}", e);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(200));
+            }
+            Err(e) => {
+                errors += 1;
+                if errors >= MAX_ACCEPT_ERRORS {
+                    log::error!("Stopping the feed server: {}", e);
+                    return;
+                }
+                log::warn!("Failed to accept a feed request: {}", e);
+                thread::sleep(Duration::from_millis(200));
+            }
+        }
+    }
+}
+
+fn handle(mut stream: TcpStream, feeds: &Mutex<HashMap<String, String>>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut host = None;
+    for line in reader.lines().take(MAX_HEADER_LINES) {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        match line.split_once(':') {
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("host") => host = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let feed = path
+        .split('?')
+        .next()
+        .and_then(|p| p.strip_prefix("/calendar/"))
+        .and_then(|p| p.strip_suffix(".ics"))
+        .map(percent_decode)
+        .and_then(|key| feeds.lock().ok()?.get(&key).cloned());
+
+    let (status, content_type, body) = match (method, feed) {
+        // another site's page resolving its own name to 127.0.0.1 (dns rebinding) must not read the feeds
+        _ if !host.as_deref().is_some_and(is_local_host) => ("403 Forbidden", "text/plain", "Forbidden".to_string()),
+        ("GET" | "HEAD", Some(ics)) => ("200 OK", "text/calendar; charset=utf-8", ics),
+        ("GET" | "HEAD", None) => ("404 Not Found", "text/plain", "Not found".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "Method not allowed".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+// the host header without its port
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1"
+}
+
+// calendar apps encode spaces and umlauts in entity names
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
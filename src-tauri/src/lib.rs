@@ -1,3 +1,8 @@
+mod ics_server;
+mod secrets;
+mod storage;
+
+use ics_server::IcsServer;
 use reqwest::{header::{HeaderMap, HeaderName, HeaderValue}, Method};
 use rustls::ClientConfig;
 use std::collections::HashMap;
@@ -5,6 +10,7 @@ use std::fmt::Write;
 
 #[derive(serde::Serialize)]
 struct ProxyResponse {
+    status: u16,
     headers: HashMap<String, Vec<String>>,
     body: String,
 }
@@ -49,6 +55,7 @@ async fn proxy(
         .await
         .map_err(|e| report(&e))?;
 
+    let status = res.status().as_u16();
     let mut resp_headers: HashMap<String, Vec<String>> = HashMap::new();
     for (name, value) in res.headers().iter() {
         if let Ok(val_str) = value.to_str() {
@@ -60,6 +67,7 @@ async fn proxy(
     }
 
     Ok(ProxyResponse {
+        status,
         headers: resp_headers,
         body: res.text().await.map_err(|e| e.to_string())?,
     })
@@ -79,9 +87,34 @@ fn report(err: &dyn std::error::Error) -> String {
 pub fn run() {
     let _ = rustls::crypto::ring::default_provider().install_default();
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![proxy])
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_log::Builder::new().level(log::LevelFilter::Info).build());
+
+    // scanning settings qr codes from another device, there is no camera flow on desktop
+    #[cfg(mobile)]
+    let builder = builder.plugin(tauri_plugin_barcode_scanner::init());
+
+    builder
+        .manage(IcsServer::default())
+        .invoke_handler(tauri::generate_handler![
+            proxy,
+            ics_server::configure_ics_server,
+            ics_server::publish_ics_feeds,
+            storage::storage_load,
+            storage::storage_set,
+            storage::storage_remove,
+            storage::storage_clear,
+            storage::records_get,
+            storage::records_put,
+            storage::records_delete,
+            storage::records_get_all,
+            storage::records_clear,
+            secrets::secret_get,
+            secrets::secret_set,
+            secrets::secret_delete
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
@@ -0,0 +1,138 @@
+use crate::storage::{data_dir, write_atomic};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tauri::AppHandle;
+
+// secrets go to the platform keyring where there is one. android has none, and desktops
+// without a running secret service fail at runtime, both use a file encrypted with a device key
+const SERVICE: &str = "com.altis.app";
+const SECRETS_FILE: &str = "secrets.json";
+const DEVICE_KEY_FILE: &str = "device.key";
+
+#[tauri::command]
+pub fn secret_get(app: AppHandle, handle: String) -> Result<Option<String>, String> {
+    #[cfg(not(target_os = "android"))]
+    match keyring::Entry::new(SERVICE, &handle).and_then(|e| e.get_password()) {
+        Ok(secret) => return Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => log_fallback(&e),
+    }
+
+    let Some(encrypted) = read_secrets(&app)?.remove(&handle) else {
+        return Ok(None);
+    };
+    decrypt(&app, &encrypted).map(Some)
+}
+
+#[tauri::command]
+pub fn secret_set(app: AppHandle, handle: String, value: String) -> Result<(), String> {
+    #[cfg(not(target_os = "android"))]
+    match keyring::Entry::new(SERVICE, &handle).and_then(|e| e.set_password(&value)) {
+        // a copy from an earlier fallback would otherwise outlive the change
+        Ok(()) => return remove_from_file(&app, &handle),
+        Err(e) => log_fallback(&e),
+    }
+
+    let mut secrets = read_secrets(&app)?;
+    secrets.insert(handle, encrypt(&app, &value)?);
+    write_secrets(&app, &secrets)
+}
+
+#[tauri::command]
+pub fn secret_delete(app: AppHandle, handle: String) -> Result<(), String> {
+    #[cfg(not(target_os = "android"))]
+    match keyring::Entry::new(SERVICE, &handle).and_then(|e| e.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => log_fallback(&e),
+    }
+
+    remove_from_file(&app, &handle)
+}
+
+#[cfg(not(target_os = "android"))]
+fn log_fallback(err: &keyring::Error) {
+    log::warn!("Keyring unavailable, using the encrypted secrets file: {}", err);
+}
+
+fn remove_from_file(app: &AppHandle, handle: &str) -> Result<(), String> {
+    let mut secrets = read_secrets(app)?;
+    if secrets.remove(handle).is_some() {
+        write_secrets(app, &secrets)?;
+    }
+    Ok(())
+}
+
+fn read_secrets(app: &AppHandle) -> Result<HashMap<String, String>, String> {
+    match fs::read_to_string(data_dir(app)?.join(SECRETS_FILE)) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn write_secrets(app: &AppHandle, secrets: &HashMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string(secrets).map_err(|e| e.to_string())?;
+    write_atomic(data_dir(app)?.join(SECRETS_FILE), &content)
+}
+
+// generated on first use, never leaves the app data directory. a key that can't be read is an error,
+// replacing it would make every secret encrypted with it unreadable for good
+fn device_cipher(app: &AppHandle) -> Result<ChaCha20Poly1305, String> {
+    let path = data_dir(app)?.join(DEVICE_KEY_FILE);
+    let key = match fs::read(&path) {
+        Ok(bytes) if bytes.len() == 32 => bytes,
+        Ok(_) => return Err("The device key is damaged".to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            write_private(&path, &key)?;
+            key.to_vec()
+        }
+        Err(e) => return Err(format!("The device key can't be read: {}", e)),
+    };
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+// only readable by the user, and never overwrites an existing file
+fn write_private(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(content).and_then(|_| file.sync_all()))
+        .map_err(|e| e.to_string())
+}
+
+// base64 of nonce followed by the ciphertext
+fn encrypt(app: &AppHandle, value: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut sealed = nonce.to_vec();
+    sealed.extend(
+        device_cipher(app)?
+            .encrypt(&nonce, value.as_bytes())
+            .map_err(|e| e.to_string())?,
+    );
+    Ok(STANDARD.encode(sealed))
+}
+
+fn decrypt(app: &AppHandle, sealed: &str) -> Result<String, String> {
+    let sealed = STANDARD.decode(sealed).map_err(|e| e.to_string())?;
+    if sealed.len() < 12 {
+        return Err("Corrupted secret".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(12);
+    let plain = device_cipher(app)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "The secret can't be decrypted with this device's key".to_string())?;
+    String::from_utf8(plain).map_err(|e| e.to_string())
+}
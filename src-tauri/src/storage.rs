@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// small key-value state (settings, cookies, ...) lives in one json file,
+// larger records like cached weeks get a file each under records/{store}/{key}
+const KV_FILE: &str = "storage.json";
+const RECORDS_DIR: &str = "records";
+
+#[tauri::command]
+pub fn storage_load(app: AppHandle) -> Result<HashMap<String, String>, String> {
+    read_kv(&app)
+}
+
+#[tauri::command]
+pub fn storage_set(app: AppHandle, key: String, value: String) -> Result<(), String> {
+    let mut kv = read_kv(&app)?;
+    kv.insert(key, value);
+    write_kv(&app, &kv)
+}
+
+#[tauri::command]
+pub fn storage_remove(app: AppHandle, key: String) -> Result<(), String> {
+    let mut kv = read_kv(&app)?;
+    if kv.remove(&key).is_some() {
+        write_kv(&app, &kv)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn storage_clear(app: AppHandle) -> Result<(), String> {
+    write_kv(&app, &HashMap::new())
+}
+
+#[tauri::command]
+pub fn records_get(app: AppHandle, store: String, key: String) -> Result<Option<String>, String> {
+    let path = record_dir(&app, &store)?.join(checked_name(&key)?);
+    match fs::read_to_string(path) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn records_put(app: AppHandle, store: String, key: String, value: String) -> Result<(), String> {
+    let dir = record_dir(&app, &store)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    write_atomic(dir.join(checked_name(&key)?), &value)
+}
+
+#[tauri::command]
+pub fn records_delete(app: AppHandle, store: String, key: String) -> Result<(), String> {
+    match fs::remove_file(record_dir(&app, &store)?.join(checked_name(&key)?)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn records_get_all(app: AppHandle, store: String) -> Result<Vec<String>, String> {
+    let Ok(entries) = fs::read_dir(record_dir(&app, &store)?) else {
+        return Ok(vec![]);
+    };
+
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_none_or(|ext| ext != "tmp"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .collect())
+}
+
+#[tauri::command]
+pub fn records_clear(app: AppHandle, store: String) -> Result<(), String> {
+    match fs::remove_dir_all(record_dir(&app, &store)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn record_dir(app: &AppHandle, store: &str) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join(RECORDS_DIR).join(checked_name(store)?))
+}
+
+fn read_kv(app: &AppHandle) -> Result<HashMap<String, String>, String> {
+    match fs::read_to_string(data_dir(app)?.join(KV_FILE)) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn write_kv(app: &AppHandle, kv: &HashMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string(kv).map_err(|e| e.to_string())?;
+    write_atomic(data_dir(app)?.join(KV_FILE), &content)
+}
+
+// a crash mid-write must not leave a truncated settings file behind
+pub(crate) fn write_atomic(path: PathBuf, content: &str) -> Result<(), String> {
+    // appended, replacing the extension would give "2026-10-12" and "2026-10-12.Class-1" the same file
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+// names come from the webview, so they must not be able to leave the data directory
+fn checked_name(name: &str) -> Result<&str, String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.ends_with(".tmp")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!("Invalid storage name: {}", name))
+    }
+}
@@ -11,12 +11,16 @@ const APP_VERIFICATION_HASH: &str =
 
 pub async fn get_b2e_token() -> Result<(String, String), ApiError> {
     let s = PersistenceManager::get_settings()?
-        .filter(|s| !s.b2e_auth.school_identifier.is_empty() && !s.b2e_auth.secret.is_empty())
+        .filter(|s| !s.b2e_auth.school_identifier.is_empty())
         .ok_or_else(|| ApiError::Authentication("B2E Credentials not set".into()))?;
+    let secret = s.b2e_auth.secret().await?;
+    if secret.is_empty() {
+        return Err(ApiError::Authentication("B2E Credentials not set".into()));
+    }
 
     let params = [
         ("mail", &s.b2e_auth.user_identifier),
-        ("password", &s.b2e_auth.secret),
+        ("password", &secret),
         ("canteen_ID", &s.b2e_auth.school_identifier),
         ("app_verification_hash", &APP_VERIFICATION_HASH.to_string()),
     ];
@@ -1,10 +1,195 @@
+use crate::data_models::clean_models::absences::{Absence, AbsenceSummary, AbsenceTotals, ExcuseStatus};
+use crate::untis::absences::AbsencesClient;
+use crate::untis::untis_week::Week;
+use chrono::{Local, NaiveDate};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
+use yew::suspense::use_future_with;
 
 #[function_component(AbsencesComponent)]
-pub fn absences() -> Html {
+pub fn absences() -> HtmlResult {
+    let range = use_state(default_range);
+    let filter = use_state(|| None::<ExcuseStatus>);
+
+    let res = use_future_with(*range, |range| async move {
+        let (start, end) = *range;
+        AbsencesClient::new()?.get_absences(start, end).await
+    })?;
+
+    let on_date_change = |is_start: bool| {
+        let range = range.clone();
+        Callback::from(move |e: Event| {
+            let val = e.target_unchecked_into::<HtmlInputElement>().value();
+            if let Ok(date) = NaiveDate::parse_from_str(&val, "%Y-%m-%d") {
+                let (start, end) = *range;
+                range.set(if is_start { (date, end.max(date)) } else { (start.min(date), date) });
+            }
+        })
+    };
+
+    let on_filter_change = {
+        let filter = filter.clone();
+        Callback::from(move |e: Event| {
+            let val = e.target_unchecked_into::<HtmlSelectElement>().value();
+            filter.set(match val.as_str() {
+                "Excused" => Some(ExcuseStatus::Excused),
+                "Unexcused" => Some(ExcuseStatus::Unexcused),
+                "Pending" => Some(ExcuseStatus::Pending),
+                _ => None,
+            });
+        })
+    };
+
+    let (start, end) = *range;
+    let selected = |s: &str| filter.map(|f| f.label()) == Some(s);
+
+    let controls = html! {
+        <div class="sticky-top p-3 mb-1 shadow-lg d-flex flex-wrap align-items-center gap-2" style="background-color: #1e1e1e; border-bottom: 1px solid #1f2227;">
+            <input type="date" class="form-control form-control-sm bg-dark text-white border-0 shadow-sm w-auto"
+                   value={start.format("%Y-%m-%d").to_string()} onchange={on_date_change(true)} />
+            <i class="bi bi-arrow-right text-secondary"></i>
+            <input type="date" class="form-control form-control-sm bg-dark text-white border-0 shadow-sm w-auto"
+                   value={end.format("%Y-%m-%d").to_string()} onchange={on_date_change(false)} />
+            <select class="form-select form-select-sm-md bg-dark text-white border-0 shadow-sm w-auto ms-md-2 select-primary-dropdown-icon" onchange={on_filter_change}>
+                <option value="All" selected={filter.is_none()}>{"All"}</option>
+                <option value="Excused" selected={selected("Excused")}>{"Excused"}</option>
+                <option value="Unexcused" selected={selected("Unexcused")}>{"Unexcused"}</option>
+                <option value="Pending" selected={selected("Pending")}>{"Pending"}</option>
+            </select>
+        </div>
+    };
+
+    let (absences, warnings) = match &*res {
+        Ok((a, w)) => (a, w),
+        Err(err) => return Ok(html! {
+            <div class="d-flex flex-column flex-grow-1 h-100">
+                { controls }
+                <div class="alert alert-danger m-3">{ err.to_string() }</div>
+            </div>
+        }),
+    };
+
+    let visible: Vec<&Absence> = absences
+        .iter()
+        .filter(|a| filter.is_none_or(|f| a.status == f))
+        .collect();
+    let summary = AbsenceSummary::from_absences(absences);
+
+    Ok(html! {
+        <div class="d-flex flex-column flex-grow-1 h-100">
+            { controls }
+            <div class="container py-3">
+                if !warnings.is_empty() {
+                    <div class="alert alert-warning small">
+                        { "The missed lessons may be incomplete:" }
+                        <ul class="mb-0">
+                            { for warnings.iter().map(|w| html! { <li>{ w }</li> }) }
+                        </ul>
+                    </div>
+                }
+                <div class="row g-2 mb-3">
+                    { render_totals("Excused", "text-success", &summary.excused) }
+                    { render_totals("Unexcused", "text-danger", &summary.unexcused) }
+                    { render_totals("Pending", "text-warning", &summary.pending) }
+                </div>
+
+                if !summary.per_subject.is_empty() {
+                    <div class="card border-primary mb-3 shadow-sm">
+                        <div class="card-header"><h5 class="mb-0">{"Missed lessons per subject"}</h5></div>
+                        <div class="card-body p-0">
+                            <table class="table table-dark table-sm mb-0 text-center">
+                                <thead>
+                                    <tr>
+                                        <th class="text-start ps-3">{"Subject"}</th>
+                                        <th class="text-success">{"Exc."}</th>
+                                        <th class="text-danger">{"Unexc."}</th>
+                                        <th class="text-warning">{"Pend."}</th>
+                                        <th>{"Total"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    { for summary.per_subject.iter().map(|(subject, counts)| html! {
+                                        <tr>
+                                            <td class="text-start ps-3 fw-bold">{ subject }</td>
+                                            <td>{ counts[0] }</td>
+                                            <td>{ counts[1] }</td>
+                                            <td>{ counts[2] }</td>
+                                            <td>{ counts.iter().sum::<usize>() }</td>
+                                        </tr>
+                                    })}
+                                </tbody>
+                            </table>
+                        </div>
+                    </div>
+                }
+
+                if visible.is_empty() {
+                    <p class="text-secondary">{"No absences in this period"}</p>
+                }
+                { for visible.iter().map(|a| render_absence(a)) }
+            </div>
+        </div>
+    })
+}
+
+fn default_range() -> (NaiveDate, NaiveDate) {
+    let today = Local::now().date_naive();
+    (Week::school_year_start(today), today)
+}
+
+fn render_totals(label: &str, color: &str, totals: &AbsenceTotals) -> Html {
+    html! {
+        <div class="col-4">
+            <div class="card bg-black border-0 shadow-sm h-100">
+                <div class="card-body text-center p-2">
+                    <div class={classes!("fs-4", "fw-bold", color.to_string())}>{ totals.lessons }</div>
+                    <div class="small text-secondary">{ format!("{} lessons", label) }</div>
+                    <div class="small text-secondary">{ format!("{} absences", totals.absences) }</div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn render_absence(absence: &Absence) -> Html {
+    let (badge, icon) = match absence.status {
+        ExcuseStatus::Excused => ("bg-success", "bi-check-circle"),
+        ExcuseStatus::Unexcused => ("bg-danger", "bi-x-circle"),
+        ExcuseStatus::Pending => ("bg-warning text-dark", "bi-hourglass-split"),
+    };
+
+    let period = if absence.start.date() == absence.end.date() {
+        format!("{} {} - {}", absence.start.format("%a %d.%m.%Y"), absence.start.format("%H:%M"), absence.end.format("%H:%M"))
+    } else {
+        format!("{} - {}", absence.start.format("%d.%m.%Y %H:%M"), absence.end.format("%d.%m.%Y %H:%M"))
+    };
+
     html! {
-        <>
-            <p>{ "Absences View" }</p>
-        </>
+        <div class="card mb-2 shadow-sm border-0" style="background-color: #2b3035;">
+            <div class="card-body text-light py-2">
+                <div class="d-flex flex-wrap align-items-center gap-2">
+                    <span class={format!("badge {}", badge)}>
+                        <i class={format!("bi {} me-1", icon)}></i>
+                        { if absence.status_label.is_empty() { absence.status.label().to_string() } else { absence.status_label.clone() } }
+                    </span>
+                    <span class="small">{ period }</span>
+                    <span class="ms-auto small text-secondary">
+                        { format!("{} lessons", absence.missed_subjects.len()) }
+                    </span>
+                </div>
+                if !absence.reason.is_empty() {
+                    <div class="small mt-1"><strong>{"Reason: "}</strong>{ &absence.reason }</div>
+                }
+                if !absence.text.is_empty() {
+                    <div class="small text-secondary">{ &absence.text }</div>
+                }
+                if !absence.excuse_text.is_empty() {
+                    <div class="small text-secondary"><strong class="text-light">{"Excuse: "}</strong>{ &absence.excuse_text }</div>
+                }
+                if !absence.missed_subjects.is_empty() {
+                    <div class="small text-secondary mt-1">{ absence.missed_subjects.join(", ") }</div>
+                }
+            </div>
+        </div>
     }
 }
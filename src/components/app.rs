@@ -1,4 +1,3 @@
-use crate::untis::untis_client::UntisClient;
 use crate::components::absences::*;
 use crate::components::auth_wrapper::AuthWrapper;
 use crate::components::book2eat::*;
@@ -8,12 +7,18 @@ use crate::components::messages::*;
 use crate::components::navbar::*;
 use crate::components::settings::settings::*;
 use crate::components::timetable::timetable::*;
-use crate::persistence_manager::PersistenceManager;
+use crate::untis::background_refresh::start_background_refresh;
+use crate::untis::ics_feed::start_feed_server;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
+// bumped whenever the background refresh fetched new data, views depending on it reload from the cache
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct DataVersion(pub u32);
+
 #[function_component(App)]
 pub fn app() -> Html {
+    let data_version = use_state(DataVersion::default);
 
     let active_tab = use_state(|| Tab::Timetable);
 
@@ -22,6 +27,28 @@ pub fn app() -> Html {
         Callback::from(move |tab: Tab| active_tab.set(tab))
     };
 
+    use_effect_with((), |_| {
+        spawn_local(async move {
+            if let Err(e) = start_feed_server().await {
+                log::warn!("Failed to start the calendar feed: {}", e);
+            }
+        });
+        || ()
+    });
+
+    {
+        let data_version = data_version.clone();
+        use_effect_with((), move |_| {
+            // the handle captured here never sees updates, so the count is kept alongside
+            let count = std::cell::Cell::new(0);
+            let interval = start_background_refresh(Callback::from(move |_| {
+                count.set(count.get() + 1);
+                data_version.set(DataVersion(count.get()));
+            }));
+            move || drop(interval)
+        });
+    }
+
     let content = match *active_tab {
         Tab::Timetable => html! { <AuthWrapper><TimetableComponent /></AuthWrapper> },
         Tab::Messages => html! { <AuthWrapper><MessagesComponent /></AuthWrapper> },
@@ -31,25 +58,16 @@ pub fn app() -> Html {
         Tab::Book2Eat => html! { <Book2EatComponent /> },
     };
 
-    if let Some(s) = PersistenceManager::get_settings().ok() && let Some(s) = s {
-        spawn_local(async move {
-            let _ = UntisClient::authenticate(
-                s.untis_auth.school_identifier,
-                s.untis_auth.user_identifier,
-                s.untis_auth.secret,
-            ).await;
-        });
-    }
-
-
     html! {
         <>
             <div class="d-flex flex-column flex-md-row vh-100 overflow-hidden bg-dark text-white">
                 <NavBar active_tab={(*active_tab).clone()} on_change={on_nav_change} />
                 <main class="d-flex flex-column flex-grow-1 overflow-y-auto">
-                    <Suspense fallback={html! { <LoadingComponent /> }}>
-                        {content}
-                    </Suspense>
+                    <ContextProvider<DataVersion> context={*data_version}>
+                        <Suspense fallback={html! { <LoadingComponent /> }}>
+                            {content}
+                        </Suspense>
+                    </ContextProvider<DataVersion>>
                 </main>
             </div>
         </>
@@ -41,10 +41,20 @@ pub fn auth_wrapper(props: &AuthWrapperProps) -> Html {
                     }
                 };
 
+                let secret = match settings.untis_auth.secret().await {
+                    Ok(secret) => secret,
+                    Err(e) => {
+                        error.set(Some(format!("Failed to read the secret: {e}")));
+                        session.set(false);
+                        return;
+                    }
+                };
+
                 match UntisClient::authenticate(
                     settings.untis_auth.school_identifier,
                     settings.untis_auth.user_identifier,
-                    settings.untis_auth.secret,
+                    secret,
+                    settings.untis_auth.mode,
                 ).await {
                     Ok(_) => session.set(true),
                     Err(e) => {
@@ -0,0 +1,27 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+// offers `content` as a file download through a temporary object url
+pub fn download_file(file_name: &str, mime_type: &str, content: &str) -> Result<(), String> {
+    let parts = js_sys::Array::of1(&content.into());
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "Failed to create file")?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(|_| "Failed to create file url")?;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("No document found")?;
+    let anchor: HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|_| "Failed to create link")?
+        .dyn_into()
+        .map_err(|_| "Failed to create link")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    let _ = Url::revoke_object_url(&url);
+    Ok(())
+}
@@ -1,6 +1,114 @@
+use crate::data_models::response_models::letto::LettoTest;
+use crate::letto::client::{get_letto_token, get_tests};
+use chrono::Local;
 use yew::prelude::*;
+use yew::suspense::use_future_with;
 
 #[function_component(LettoComponent)]
-pub fn letto() -> Html {
-    html! { <div>{"Letto View"}</div> }
+pub fn letto() -> HtmlResult {
+    let reload_trigger = use_state(|| 0);
+
+    let res = use_future_with(*reload_trigger, |_| async move {
+        let (base, token) = get_letto_token()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        get_tests(base, token)
+            .await
+            .map_err(|e| e.to_string())
+    })?;
+
+    let on_reload = {
+        let trigger = reload_trigger.clone();
+        Callback::from(move |_| trigger.set(*trigger + 1))
+    };
+
+    match &*res {
+        Ok(tests) => {
+            let (mut open, mut past): (Vec<&LettoTest>, Vec<&LettoTest>) = tests.iter().partition(|t| t.open);
+            open.sort_by_key(|t| t.due().unwrap_or(chrono::NaiveDateTime::MAX));
+            past.sort_by_key(|t| std::cmp::Reverse(t.due()));
+
+            Ok(html! {
+                <div class="container py-4">
+                    <div class="d-flex justify-content-between align-items-center mb-3">
+                        <h4 class="text-white mb-0">{"Open"}</h4>
+                        <button class="btn btn-outline-primary" onclick={on_reload}>
+                            <i class="bi bi-arrow-clockwise me-sm-1"></i>
+                            <span class="d-none d-sm-inline">{"Reload"}</span>
+                        </button>
+                    </div>
+                    if open.is_empty() {
+                        <p class="text-secondary">{"Nothing due"}</p>
+                    }
+                    { for open.iter().map(|t| render_test(t)) }
+
+                    <h4 class="text-white mt-4 mb-3">{"Past"}</h4>
+                    if past.is_empty() {
+                        <p class="text-secondary">{"No past assignments"}</p>
+                    }
+                    { for past.iter().map(|t| render_test(t)) }
+                </div>
+            })
+        }
+        Err(err_msg) => Ok(html! {
+            <div class="container mt-5">
+                <div class="alert alert-danger shadow-sm">
+                    <strong>{"Error:"}</strong> { format!(" {}", err_msg) }
+                </div>
+            </div>
+        })
+    }
+}
+
+fn render_test(test: &LettoTest) -> Html {
+    let now = Local::now().naive_local();
+    let due = test.due().map(|d| {
+        let days = (d.date() - now.date()).num_days();
+        let relative = match days {
+            _ if d < now => "overdue".to_string(),
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            n => format!("in {} days", n),
+        };
+        (d, relative, test.open && days <= 1)
+    });
+
+    let icon = match test.r#type.to_uppercase().as_str() {
+        "TEST" | "EXAM" => "bi-pencil-square",
+        "HOMEWORK" => "bi-house",
+        _ => "bi-journal-text",
+    };
+
+    html! {
+        <div class="card mb-2 shadow-sm border-0" style="background-color: #2b3035;">
+            <div class="card-body text-light py-2">
+                <div class="d-flex align-items-center gap-2">
+                    <i class={format!("bi {} text-primary", icon)}></i>
+                    <strong class="text-truncate">{ &test.name }</strong>
+                    if test.has_result() {
+                        <span class="badge bg-primary text-black ms-auto">
+                            { match (test.points, test.max_points) {
+                                (Some(p), Some(m)) => format!("{} / {}", p, m),
+                                (Some(p), None) => format!("{}", p),
+                                _ => String::new(),
+                            }}
+                            if let Some(grade) = test.grade.as_ref().filter(|g| !g.is_empty()) {
+                                { format!(" ({})", grade) }
+                            }
+                        </span>
+                    }
+                </div>
+                <div class="d-flex small text-secondary">
+                    <span class="text-truncate flex-grow-1">{ &test.course_name }</span>
+                    if let Some((date, relative, urgent)) = due {
+                        <span class={classes!("ms-2", "text-nowrap", if urgent { "text-warning" } else { "" })}>
+                            <i class="bi bi-clock me-1"></i>
+                            { format!("{} ({})", date.format("%d.%m.%Y %H:%M"), relative) }
+                        </span>
+                    }
+                </div>
+            </div>
+        </div>
+    }
 }
@@ -1,10 +1,242 @@
+use crate::components::loading::LoadingComponent;
+use crate::data_models::clean_models::messages::{Mailbox, MessageDetail, MessageFolder, MessagePerson, MessageThread};
+use crate::untis::messages::MessagesClient;
+use std::collections::HashSet;
 use yew::prelude::*;
+use yew::suspense::use_future_with;
 
 #[function_component(MessagesComponent)]
-pub fn messages() -> Html {
+pub fn messages() -> HtmlResult {
+    let reload_trigger = use_state(|| 0);
+    let folder = use_state(|| MessageFolder::Inbox);
+    let selected_thread = use_state(|| None::<i64>);
+    let opened = use_state(HashSet::<i64>::new);
+
+    let res = use_future_with(*reload_trigger, |_| async move {
+        MessagesClient::new()?.get_mailbox().await
+    })?;
+
+    let mailbox: &Mailbox = match &*res {
+        Ok(m) => m,
+        Err(err) => return Ok(html! { <div class="alert alert-danger m-3">{ err.to_string() }</div> }),
+    };
+
+    let threads = mailbox.threads(*folder);
+    let active_thread = selected_thread
+        .as_ref()
+        .and_then(|id| threads.iter().find(|t| t.id == *id))
+        .cloned();
+
+    let on_reload = {
+        let trigger = reload_trigger.clone();
+        let selected_thread = selected_thread.clone();
+        Callback::from(move |_: MouseEvent| {
+            selected_thread.set(None);
+            trigger.set(*trigger + 1);
+        })
+    };
+
+    let on_back = {
+        let selected_thread = selected_thread.clone();
+        Callback::from(move |_: MouseEvent| selected_thread.set(None))
+    };
+
+    let folder_button = |f: MessageFolder, label: &'static str, icon: &'static str| {
+        let folder = folder.clone();
+        let selected_thread = selected_thread.clone();
+        let unread = mailbox.folder(f).iter().filter(|m| !m.is_read && !opened.contains(&m.id)).count();
+        let active = *folder == f;
+        html! {
+            <button
+                class={classes!("btn", "btn-sm", "me-2", if active { "btn-primary" } else { "btn-outline-primary" })}
+                onclick={Callback::from(move |_| { folder.set(f); selected_thread.set(None); })}
+            >
+                <i class={classes!("bi", icon, "me-sm-1")}></i>
+                <span class="d-none d-sm-inline">{ label }</span>
+                if unread > 0 {
+                    <span class="badge rounded-pill bg-danger ms-1">{ unread }</span>
+                }
+            </button>
+        }
+    };
+
+    let on_thread_click = {
+        let selected_thread = selected_thread.clone();
+        let opened = opened.clone();
+        Callback::from(move |thread: MessageThread| {
+            let mut ids = (*opened).clone();
+            ids.extend(thread.messages.iter().map(|m| m.id));
+            opened.set(ids);
+            selected_thread.set(Some(thread.id));
+        })
+    };
+
+    Ok(html! {
+        <div class="d-flex flex-column flex-grow-1 h-100">
+            <div class="sticky-top p-3 mb-1 shadow-lg d-flex align-items-center" style="background-color: #1e1e1e; border-bottom: 1px solid #1f2227;">
+                { folder_button(MessageFolder::Inbox, "Inbox", "bi-inbox") }
+                { folder_button(MessageFolder::Sent, "Sent", "bi-send") }
+                { folder_button(MessageFolder::ReadConfirmations, "Read confirmations", "bi-check2-square") }
+                <button class="btn btn-outline-primary ms-auto" onclick={on_reload}>
+                    <i class="bi bi-arrow-clockwise me-sm-1"></i>
+                    <span class="d-none d-sm-inline">{"Reload"}</span>
+                </button>
+            </div>
+
+            <div class="d-flex flex-grow-1 overflow-hidden">
+                <div
+                    class={classes!("flex-column", "overflow-y-auto", "border-end", "border-secondary",
+                        if active_thread.is_some() { "d-none d-md-flex" } else { "d-flex" })}
+                    style="flex: 0 0 100%; max-width: 420px; min-width: 0;"
+                >
+                    if threads.is_empty() {
+                        <p class="text-secondary p-3">{"No messages"}</p>
+                    }
+                    { for threads.iter().map(|thread| {
+                        let is_active = active_thread.as_ref().is_some_and(|t| t.id == thread.id);
+                        let unread = thread.messages.iter().any(|m| !m.is_read && !opened.contains(&m.id));
+                        render_thread_item(thread, is_active, unread, *folder, on_thread_click.clone())
+                    })}
+                </div>
+
+                <div class={classes!("flex-grow-1", "overflow-y-auto", "p-3",
+                    if active_thread.is_some() { "d-block" } else { "d-none d-md-block" })}
+                >
+                    if let Some(thread) = &active_thread {
+                        <button class="btn btn-sm btn-outline-secondary mb-3 d-md-none" onclick={on_back}>
+                            <i class="bi bi-chevron-left me-1"></i>{"Back"}
+                        </button>
+                        <h4 class="text-white mb-3">{ &thread.topic }</h4>
+                        { for thread.messages.iter().map(|m| html! {
+                            <Suspense key={m.id} fallback={html! { <LoadingComponent /> }}>
+                                <MessageDetailView id={m.id} />
+                            </Suspense>
+                        })}
+                    } else {
+                        <p class="text-secondary">{"Select a message"}</p>
+                    }
+                </div>
+            </div>
+        </div>
+    })
+}
+
+fn render_thread_item(
+    thread: &MessageThread,
+    is_active: bool,
+    unread: bool,
+    folder: MessageFolder,
+    on_click: Callback<MessageThread>,
+) -> Html {
+    let Some(latest) = thread.latest() else {
+        return html! {};
+    };
+    let counterpart = match folder {
+        MessageFolder::Sent => latest.recipients.iter().map(|p| p.display_name.clone()).collect::<Vec<_>>().join(", "),
+        _ => latest.sender.as_ref().map(|p| p.display_name.clone()).unwrap_or_default(),
+    };
+    let has_attachments = thread.messages.iter().any(|m| m.has_attachments);
+    let thread_clone = thread.clone();
+
     html! {
-        <>
-            <p>{ "Messages View" }</p>
-        </>
+        <button
+            class={classes!("btn", "text-start", "rounded-0", "border-0", "border-bottom", "border-secondary", "p-3", "text-white",
+                if is_active { "selected-gradient" } else { "" })}
+            onclick={Callback::from(move |_| on_click.emit(thread_clone.clone()))}
+        >
+            <div class="d-flex align-items-center mb-1">
+                if unread {
+                    <i class="bi bi-circle-fill text-primary me-2" style="font-size: 0.5rem;"></i>
+                }
+                <span class={classes!("text-truncate", "flex-grow-1", if unread { "fw-bold" } else { "" })}>{ &thread.topic }</span>
+                if has_attachments {
+                    <i class="bi bi-paperclip text-secondary ms-2"></i>
+                }
+                if thread.messages.len() > 1 {
+                    <span class="badge rounded-pill bg-secondary text-black ms-2">{ thread.messages.len() }</span>
+                }
+            </div>
+            <div class="d-flex small text-secondary">
+                <span class="text-truncate flex-grow-1">{ counterpart }</span>
+                <span class="ms-2 text-nowrap">{ latest.sent.format("%d.%m. %H:%M").to_string() }</span>
+            </div>
+            <div class="small text-secondary text-truncate">{ &latest.preview }</div>
+        </button>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct MessageDetailProps {
+    id: i64,
+}
+
+#[function_component(MessageDetailView)]
+fn message_detail_view(props: &MessageDetailProps) -> HtmlResult {
+    let res = use_future_with(props.id, |id| async move {
+        MessagesClient::new()?.get_message(*id).await
+    })?;
+
+    Ok(match &*res {
+        Ok(detail) => render_detail(detail, false),
+        Err(err) => html! { <div class="alert alert-danger">{ err.to_string() }</div> },
+    })
+}
+
+fn render_detail(detail: &MessageDetail, nested: bool) -> Html {
+    let people = |p: &[MessagePerson]| p.iter().map(|p| p.display_name.clone()).collect::<Vec<_>>().join(", ");
+
+    html! {
+        <div class={classes!("card", "shadow-sm", "border-0", "mb-3", if nested { "ms-3" } else { "" })}
+             style="background-color: #2b3035; border-left: 5px solid #fac444 !important;">
+            <div class="card-body text-light">
+                <div class="d-flex flex-wrap align-items-center gap-2 mb-2">
+                    <i class="bi bi-person-circle text-primary"></i>
+                    <strong>{ detail.sender.as_ref().map(|s| s.display_name.clone()).unwrap_or_default() }</strong>
+                    <span class="text-secondary small ms-auto">
+                        <i class="bi bi-clock me-1"></i>
+                        { detail.sent.format("%d.%m.%Y %H:%M").to_string() }
+                    </span>
+                </div>
+                if !detail.recipients.is_empty() {
+                    <div class="small text-secondary mb-2">{ format!("To: {}", people(&detail.recipients)) }</div>
+                }
+
+                <div class="mt-2 pt-2 border-top border-secondary" style="white-space: pre-wrap;">{ &detail.content }</div>
+
+                if !detail.attachments.is_empty() {
+                    <div class="mt-3 d-flex flex-wrap gap-2">
+                        { for detail.attachments.iter().map(|a| if a.url.is_empty() {
+                            html! {
+                                <span class="btn btn-sm btn-outline-secondary p-1 px-2 disabled">
+                                    <i class="bi bi-paperclip me-1"></i>{ &a.name }
+                                </span>
+                            }
+                        } else {
+                            html! {
+                                <a href={a.url.clone()} target="_blank" rel="noopener noreferrer" class="btn btn-sm btn-outline-info p-1 px-2 text-decoration-none">
+                                    <i class="bi bi-paperclip me-1"></i>{ &a.name }
+                                </a>
+                            }
+                        })}
+                    </div>
+                }
+
+                if let Some(confirmation) = &detail.confirmation {
+                    <div class="mt-3">
+                        if let Some(at) = confirmation.confirmed_at {
+                            <span class="badge bg-success">
+                                <i class="bi bi-check2-all me-1"></i>{ format!("Confirmed {}", at.format("%d.%m.%Y %H:%M")) }
+                            </span>
+                        } else {
+                            <span class="badge bg-warning text-dark">
+                                <i class="bi bi-exclamation-circle me-1"></i>{"Read confirmation requested"}
+                            </span>
+                        }
+                    </div>
+                }
+
+                { for detail.history.iter().map(|h| render_detail(h, true)) }
+            </div>
+        </div>
     }
 }
@@ -8,4 +8,5 @@ mod letto;
 mod book2eat;
 mod auth_wrapper;
 mod loading;
-mod qr_code;
\ No newline at end of file
+mod qr_code;
+mod download;
\ No newline at end of file
@@ -1,31 +1,70 @@
+use wasm_bindgen_futures::spawn_local;
 use web_sys::{HtmlInputElement, InputEvent, MouseEvent};
-use yew::{function_component, html, use_state, Callback, Html, Properties, TargetCast, UseStateHandle};
+use yew::{classes, function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast, UseStateHandle};
 use crate::components::settings::settings_card::SettingsCard;
-use crate::persistence_manager::AuthSettings;
+use crate::persistence_manager::{AuthSettings, UntisAuthMode};
+use crate::qr_scanner::{can_scan, scan_qr};
+use crate::secret_store::{SecretStore, B2E_SECRET, LETTO_SECRET, UNTIS_SECRET};
+use crate::untis::setschool_uri::SetSchoolUri;
 
 #[derive(PartialEq)]
 pub enum AuthType {
     Untis,
     Book2Eat,
+    Letto,
+}
+
+impl AuthType {
+    pub fn secret_handle(&self) -> &'static str {
+        match self {
+            AuthType::Untis => UNTIS_SECRET,
+            AuthType::Book2Eat => B2E_SECRET,
+            AuthType::Letto => LETTO_SECRET,
+        }
+    }
 }
 
 #[derive(Properties, PartialEq)]
 pub struct AuthCardProps {
     pub r#type: AuthType,
     pub initial: AuthSettings,
-    pub on_save: Callback<AuthSettings>,
+    pub on_save: Callback<(AuthSettings, String)>, // settings and the secret to store
 }
 
 #[function_component(AuthSettingsCard)]
 pub fn auth_settings_card(props: &AuthCardProps) -> Html {
     let school = use_state(|| props.initial.school_identifier.clone());
     let user = use_state(|| props.initial.user_identifier.clone());
-    let secret = use_state(|| props.initial.secret.clone());
+    let secret = use_state(String::new);
+    let saved_secret = use_state(String::new);
+    let mode = use_state(|| props.initial.mode);
     let secret_visible = use_state(|| false);
+    let link = use_state(String::new);
+    let link_error = use_state(|| None::<String>);
+
+    {
+        let secret = secret.clone();
+        let saved_secret = saved_secret.clone();
+        let handle = props.r#type.secret_handle();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match SecretStore::get(handle).await {
+                    Ok(value) => {
+                        let value = value.unwrap_or_default();
+                        secret.set(value.clone());
+                        saved_secret.set(value);
+                    }
+                    Err(e) => log::warn!("Failed to load the secret: {}", e),
+                }
+            });
+            || ()
+        });
+    }
 
     let is_dirty = *school != props.initial.school_identifier
         || *user != props.initial.user_identifier
-        || *secret != props.initial.secret;
+        || *secret != *saved_secret
+        || *mode != props.initial.mode;
 
     let on_input = |state: UseStateHandle<String>| {
         Callback::from(move |e: InputEvent| {
@@ -34,36 +73,133 @@ pub fn auth_settings_card(props: &AuthCardProps) -> Html {
         })
     };
 
+    // fills the fields from a webuntis mobile access link, they still have to be saved
+    let apply_link = {
+        let school = school.clone();
+        let user = user.clone();
+        let secret = secret.clone();
+        let mode = mode.clone();
+        let link = link.clone();
+        let link_error = link_error.clone();
+        Callback::from(move |value: String| match SetSchoolUri::parse(&value) {
+            Ok(parsed) => {
+                school.set(parsed.school);
+                user.set(parsed.user);
+                secret.set(parsed.key);
+                mode.set(UntisAuthMode::Secret);
+                link.set(String::new());
+                link_error.set(None);
+            }
+            Err(e) => {
+                link.set(value);
+                link_error.set(Some(e));
+            }
+        })
+    };
+
+    let on_apply_link = {
+        let apply_link = apply_link.clone();
+        let link = link.clone();
+        Callback::from(move |_| apply_link.emit((*link).clone()))
+    };
+
+    let on_scan_link = {
+        let apply_link = apply_link.clone();
+        let link_error = link_error.clone();
+        Callback::from(move |_| {
+            let apply_link = apply_link.clone();
+            let link_error = link_error.clone();
+            spawn_local(async move {
+                match scan_qr().await {
+                    Ok(content) => apply_link.emit(content),
+                    Err(e) => link_error.set(Some(format!("Scanning failed: {}", e))),
+                }
+            });
+        })
+    };
+
+    let on_mode = |new_mode: UntisAuthMode| {
+        let mode = mode.clone();
+        Callback::from(move |_| mode.set(new_mode))
+    };
+
     let handle_save = {
         let on_save = props.on_save.clone();
+        let mode = mode.clone();
         let school = school.clone();
         let user = user.clone();
         let secret = secret.clone();
+        let saved_secret = saved_secret.clone();
+        let handle = props.r#type.secret_handle();
         Callback::from(move |e: MouseEvent| {
             e.prevent_default();
-            on_save.emit(AuthSettings {
-                school_identifier: (*school).clone(),
-                user_identifier: (*user).clone(),
-                secret: (*secret).clone(),
-            });
+            saved_secret.set((*secret).clone());
+            on_save.emit((
+                AuthSettings {
+                    school_identifier: (*school).clone(),
+                    user_identifier: (*user).clone(),
+                    secret_handle: handle.to_string(),
+                    mode: *mode,
+                },
+                (*secret).clone(),
+            ));
         })
     };
 
+    let secret_label = match props.r#type {
+        AuthType::Untis if *mode == UntisAuthMode::Secret => "Secret",
+        _ => "Password",
+    };
+
     let secret_icon_class = if *secret_visible { "bi bi-eye text-primary" } else { "bi bi-eye-slash text-secondary" };
 
     html! {
-        <SettingsCard title={ match props.r#type {AuthType::Untis => "Untis-Authentication", AuthType::Book2Eat => "Book2Eat-Authentication"}}>
+        <SettingsCard title={ match props.r#type {AuthType::Untis => "Untis-Authentication", AuthType::Book2Eat => "Book2Eat-Authentication", AuthType::Letto => "Letto-Authentication"}}>
             <form>
+                if props.r#type == AuthType::Untis {
+                    <div class="btn-group w-100 mb-3" role="group">
+                        <button type="button" onclick={on_mode(UntisAuthMode::Secret)}
+                                class={if *mode == UntisAuthMode::Secret {"btn btn-primary"} else {"btn btn-outline-primary"}}>
+                            {"Secret"}
+                        </button>
+                        <button type="button" onclick={on_mode(UntisAuthMode::Password)}
+                                class={if *mode == UntisAuthMode::Password {"btn btn-primary"} else {"btn btn-outline-primary"}}>
+                            {"Password"}
+                        </button>
+                    </div>
+                }
+                if props.r#type == AuthType::Untis && *mode == UntisAuthMode::Secret {
+                    <div class="mb-3">
+                        <label class="form-label small text-secondary">{"Mobile Access Link"}</label>
+                        <div class="input-group has-validation">
+                            <input type="text"
+                                   placeholder="untis://setschool?..."
+                                   value={(*link).clone()}
+                                   oninput={on_input(link.clone())}
+                                   class={classes!("form-control", link_error.is_some().then_some("is-invalid"))}/>
+                            if can_scan() {
+                                <button class="btn btn-outline-secondary" type="button" onclick={on_scan_link}>
+                                    <i class="bi bi-qr-code-scan"></i>
+                                </button>
+                            }
+                            <button class="btn btn-outline-secondary" type="button" disabled={link.trim().is_empty()} onclick={on_apply_link}>
+                                {"Apply"}
+                            </button>
+                            <div class="invalid-feedback">{ (*link_error).clone().unwrap_or_default() }</div>
+                        </div>
+                        <div class="form-text">{"WebUntis: Profile → Data Access → Display, then copy or scan the QR code"}</div>
+                    </div>
+                }
                 <div class="mb-3">
-                    <label class="form-label small text-secondary">{ match props.r#type {AuthType::Untis => "School", AuthType::Book2Eat => "Canteen-ID"}}</label>
+                    <label class="form-label small text-secondary">{ match props.r#type {AuthType::Untis => "School", AuthType::Book2Eat => "Canteen-ID", AuthType::Letto => "Server"}}</label>
                     <input type="text" value={(*school).clone()} oninput={on_input(school.clone())} class="form-control" />
                 </div>
                 <div class="mb-3">
-                    <label class="form-label small text-secondary">{ match props.r#type {AuthType::Untis => "Username", AuthType::Book2Eat => "Mail"}}</label>
+                    <label class="form-label small text-secondary">{ match props.r#type {AuthType::Untis => "Username", AuthType::Book2Eat => "Mail", AuthType::Letto => "Username"}}</label>
                     <input type="text" value={(*user).clone()} oninput={on_input(user.clone())} class="form-control" />
                 </div>
                 <div class="mb-3">
-                    <label class="form-label small text-secondary">{ match props.r#type {AuthType::Untis => "Secret", AuthType::Book2Eat => "Password"}}</label>
+                    <label class="form-label small text-secondary">{ secret_label }</label>
                     <div class="input-group">
                         <input type={if *secret_visible {"text"} else {"password"}}
                                value={(*secret).clone()}
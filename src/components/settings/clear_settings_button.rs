@@ -1,13 +1,26 @@
 use crate::persistence_manager::PersistenceManager;
+use crate::secret_store::{SecretStore, ALL_SECRETS};
+use crate::timetable_store::TimetableStore;
+use wasm_bindgen_futures::spawn_local;
 use yew::{function_component, html, use_state, Callback, Html};
 
 #[function_component(ClearSettingsButton)]
 pub fn clear_settings_button() -> Html {
     let show_modal = use_state(|| false);
+    let cache_usage = use_state(|| None::<(usize, u32)>);
 
     let toggle_modal = {
         let show_modal = show_modal.clone();
-        Callback::from(move |_| show_modal.set(!*show_modal))
+        let cache_usage = cache_usage.clone();
+        Callback::from(move |_| {
+            if !*show_modal {
+                let cache_usage = cache_usage.clone();
+                spawn_local(async move {
+                    cache_usage.set(TimetableStore::usage().await.ok());
+                });
+            }
+            show_modal.set(!*show_modal)
+        })
     };
 
     let confirm_clear = {
@@ -15,7 +28,13 @@ pub fn clear_settings_button() -> Html {
         Callback::from(move |_| {
             let _ = PersistenceManager::clear_storage();
             show_modal.set(false);
-            let _ = web_sys::window().unwrap().location().reload();
+            spawn_local(async {
+                for handle in ALL_SECRETS {
+                    let _ = SecretStore::delete(handle).await;
+                }
+                let _ = TimetableStore::clear().await;
+                let _ = web_sys::window().unwrap().location().reload();
+            });
         })
     };
 
@@ -35,6 +54,11 @@ pub fn clear_settings_button() -> Html {
                             </div>
                             <div class="modal-body">
                                 <p>{"This will delete all settings, including saved credentials and preferences. This action cannot be undone."}</p>
+                                if let Some((weeks, bytes)) = *cache_usage {
+                                    <p class="small text-secondary mb-0">
+                                        { format!("The timetable cache ({} weeks, {:.1} MB) is removed as well.", weeks, bytes as f64 / 1024.0 / 1024.0) }
+                                    </p>
+                                }
                             </div>
                             <div class="modal-footer">
                                 <button type="button" class="btn btn-success" onclick={toggle_modal}>{"Cancel"}</button>
@@ -0,0 +1,82 @@
+use crate::components::settings::settings_card::SettingsCard;
+use crate::persistence_manager::FeedSettings;
+use web_sys::{HtmlInputElement, InputEvent, MouseEvent};
+use yew::{classes, function_component, html, use_state, Callback, Html, Properties, TargetCast};
+
+#[derive(Properties, PartialEq)]
+pub struct FeedCardProps {
+    pub initial: FeedSettings,
+    pub on_save: Callback<FeedSettings>,
+}
+
+#[function_component(FeedSettingsCard)]
+pub fn feed_settings_card(props: &FeedCardProps) -> Html {
+    let enabled = use_state(|| props.initial.enabled);
+    let port = use_state(|| props.initial.port.to_string());
+
+    // ports below 1024 need elevated privileges on most systems
+    let parsed_port = port.parse::<u16>().ok().filter(|p| *p >= 1024);
+    let is_dirty = *enabled != props.initial.enabled || parsed_port != Some(props.initial.port);
+
+    let on_toggle = {
+        let enabled = enabled.clone();
+        Callback::from(move |_| enabled.set(!*enabled))
+    };
+
+    let on_port_input = {
+        let port = port.clone();
+        Callback::from(move |e: InputEvent| {
+            port.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let handle_save = {
+        let on_save = props.on_save.clone();
+        let enabled = *enabled;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if let Some(port) = parsed_port {
+                on_save.emit(FeedSettings { enabled, port });
+            }
+        })
+    };
+
+    html! {
+        <SettingsCard title="Calendar Feed">
+            <form>
+                <div class="mb-3 form-check">
+                    <input
+                        type="checkbox"
+                        class="form-check-input"
+                        id="feedCheck"
+                        checked={*enabled}
+                        onclick={on_toggle}
+                    />
+                    <label class="form-check-label small text-secondary" for="feedCheck" style="cursor: pointer;">
+                        {"Serve timetables as a local calendar feed"}
+                    </label>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label small text-secondary">{"Port"}</label>
+                    <input
+                        type="number"
+                        class={classes!("form-control", parsed_port.is_none().then_some("is-invalid"))}
+                        min="1024"
+                        max="65535"
+                        value={(*port).clone()}
+                        oninput={on_port_input}
+                    />
+                    <div class="invalid-feedback">{"Use a port between 1024 and 65535"}</div>
+                </div>
+                if let Some(p) = parsed_port {
+                    <p class="small text-secondary mb-3">
+                        { format!("Subscribe to http://127.0.0.1:{}/calendar/class/<name>.ics (or teacher/room)", p) }
+                    </p>
+                }
+                <button onclick={handle_save} disabled={!is_dirty || parsed_port.is_none()} class={if is_dirty {"btn btn-primary w-100"} else {"btn btn-outline-success w-100"}}>
+                    {if is_dirty {"Save Changes"} else {"Saved"}}
+                </button>
+            </form>
+        </SettingsCard>
+    }
+}
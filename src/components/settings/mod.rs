@@ -2,4 +2,8 @@ mod auth_settings_card;
 pub mod settings;
 mod settings_card;
 mod clear_settings_button;
+mod transfer_settings_button;
 mod visual_settings;
+mod feed_settings;
+mod refresh_settings;
+mod timetable_settings;
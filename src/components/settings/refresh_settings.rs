@@ -0,0 +1,111 @@
+use crate::components::settings::settings_card::SettingsCard;
+use crate::persistence_manager::RefreshSettings;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent, MouseEvent};
+use yew::{function_component, html, use_state, Callback, Html, Properties, TargetCast, UseStateHandle};
+
+#[derive(Properties, PartialEq)]
+pub struct RefreshCardProps {
+    pub initial: RefreshSettings,
+    pub on_save: Callback<RefreshSettings>,
+}
+
+#[function_component(RefreshSettingsCard)]
+pub fn refresh_settings_card(props: &RefreshCardProps) -> Html {
+    let enabled = use_state(|| props.initial.enabled);
+    let interval = use_state(|| props.initial.interval_minutes.to_string());
+    let quiet_start = use_state(|| props.initial.quiet_start_hour);
+    let quiet_end = use_state(|| props.initial.quiet_end_hour);
+
+    let parsed_interval = interval.parse::<u32>().ok().filter(|i| *i >= 5);
+    let current = parsed_interval.map(|interval_minutes| RefreshSettings {
+        enabled: *enabled,
+        interval_minutes,
+        quiet_start_hour: *quiet_start,
+        quiet_end_hour: *quiet_end,
+    });
+    let is_dirty = current.as_ref() != Some(&props.initial);
+    let is_valid = current.is_some();
+
+    let on_toggle = {
+        let enabled = enabled.clone();
+        Callback::from(move |_| enabled.set(!*enabled))
+    };
+
+    let on_interval_input = {
+        let interval = interval.clone();
+        Callback::from(move |e: InputEvent| {
+            interval.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_hour_change = |state: UseStateHandle<u32>| {
+        Callback::from(move |e: Event| {
+            if let Ok(hour) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                state.set(hour);
+            }
+        })
+    };
+
+    let hour_options = |selected: u32| {
+        html! {
+            { for (0..24u32).map(|h| html! {
+                <option value={h.to_string()} selected={h == selected}>{ format!("{:02}:00", h) }</option>
+            })}
+        }
+    };
+
+    let handle_save = {
+        let on_save = props.on_save.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if let Some(settings) = current.clone() {
+                on_save.emit(settings);
+            }
+        })
+    };
+
+    html! {
+        <SettingsCard title="Background Refresh">
+            <form>
+                <div class="mb-3 form-check">
+                    <input
+                        type="checkbox"
+                        class="form-check-input"
+                        id="refreshCheck"
+                        checked={*enabled}
+                        onclick={on_toggle}
+                    />
+                    <label class="form-check-label small text-secondary" for="refreshCheck" style="cursor: pointer;">
+                        {"Refresh this and next week in the background"}
+                    </label>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label small text-secondary">{"Interval (minutes)"}</label>
+                    <input
+                        type="number"
+                        min="5"
+                        class={if parsed_interval.is_some() { "form-control" } else { "form-control is-invalid" }}
+                        value={(*interval).clone()}
+                        oninput={on_interval_input}
+                    />
+                    <div class="invalid-feedback">{"At least 5 minutes"}</div>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label small text-secondary">{"Quiet hours"}</label>
+                    <div class="input-group">
+                        <select class="form-select" onchange={on_hour_change(quiet_start.clone())}>
+                            { hour_options(*quiet_start) }
+                        </select>
+                        <span class="input-group-text">{"to"}</span>
+                        <select class="form-select" onchange={on_hour_change(quiet_end.clone())}>
+                            { hour_options(*quiet_end) }
+                        </select>
+                    </div>
+                </div>
+                <button onclick={handle_save} disabled={!is_dirty || !is_valid} class={if is_dirty {"btn btn-primary w-100"} else {"btn btn-outline-success w-100"}}>
+                    {if is_dirty {"Save Changes"} else {"Saved"}}
+                </button>
+            </form>
+        </SettingsCard>
+    }
+}
@@ -1,9 +1,15 @@
 use crate::untis::untis_client::UntisClient;
 use crate::components::settings::auth_settings_card::{AuthSettingsCard, AuthType};
 use crate::components::settings::clear_settings_button::ClearSettingsButton;
+use crate::components::settings::transfer_settings_button::TransferSettingsButton;
 use crate::persistence_manager::*;
+use crate::secret_store::SecretStore;
 use yew::prelude::*;
 use crate::components::settings::visual_settings::VisualSettingsCard;
+use crate::components::settings::feed_settings::FeedSettingsCard;
+use crate::components::settings::refresh_settings::RefreshSettingsCard;
+use crate::components::settings::timetable_settings::TimetableSettingsCard;
+use crate::untis::ics_feed::start_feed_server;
 
 #[function_component(SettingsComponent)]
 pub fn settings() -> Html {
@@ -34,22 +40,38 @@ pub fn settings() -> Html {
         })
     };
 
-    let on_auth_save = {
+    // the secret goes to the secret store first, the settings only reference it
+    let save_auth = {
         let update_settings = update_settings.clone();
-        Callback::from(move |new_auth: AuthSettings| {
-            let auth_clone = new_auth.clone();
-            update_settings.emit(Box::new(move |s| s.untis_auth = new_auth));
+        let error_message = error_message.clone();
+        move |assign: fn(&mut Settings, AuthSettings), on_saved: Callback<(AuthSettings, String)>| {
+            let update_settings = update_settings.clone();
+            let error_message = error_message.clone();
+            Callback::from(move |(new_auth, secret): (AuthSettings, String)| {
+                let update_settings = update_settings.clone();
+                let error_message = error_message.clone();
+                let on_saved = on_saved.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(err) = SecretStore::set(&new_auth.secret_handle, &secret).await {
+                        error_message.set(Some(format!("Saving the secret failed: {}", err)));
+                        return;
+                    }
+                    let auth_clone = new_auth.clone();
+                    update_settings.emit(Box::new(move |s| assign(s, new_auth)));
+                    on_saved.emit((auth_clone, secret));
+                });
+            })
+        }
+    };
 
+    let on_auth_save = save_auth(
+        |s, auth| s.untis_auth = auth,
+        Callback::from(|(auth, secret): (AuthSettings, String)| {
             wasm_bindgen_futures::spawn_local(async move {
-                let _ = UntisClient::authenticate(
-                    auth_clone.school_identifier,
-                    auth_clone.user_identifier,
-                    auth_clone.secret,
-                )
-                    .await;
+                let _ = UntisClient::authenticate(auth.school_identifier, auth.user_identifier, secret, auth.mode).await;
             });
-        })
-    };
+        }),
+    );
 
     let on_visual_save = {
         let update_settings = update_settings.clone();
@@ -58,10 +80,36 @@ pub fn settings() -> Html {
         })
     };
 
-    let b2e_save = {
+    let b2e_save = save_auth(|s, auth| s.b2e_auth = auth, Callback::noop());
+
+    let letto_save = save_auth(|s, auth| s.letto_auth = auth, Callback::noop());
+
+    let feed_save = {
+        let update_settings = update_settings.clone();
+        let error_message = error_message.clone();
+        Callback::from(move |new_feed: FeedSettings| {
+            update_settings.emit(Box::new(move |s| s.feed_settings = new_feed));
+
+            let error_message = error_message.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(err) = start_feed_server().await {
+                    error_message.set(Some(format!("Calendar feed failed: {}", err)));
+                }
+            });
+        })
+    };
+
+    let refresh_save = {
+        let update_settings = update_settings.clone();
+        Callback::from(move |new_refresh: RefreshSettings| {
+            update_settings.emit(Box::new(move |s| s.refresh_settings = new_refresh));
+        })
+    };
+
+    let timetable_save = {
         let update_settings = update_settings.clone();
-        Callback::from(move |new_auth: AuthSettings| {
-            update_settings.emit(Box::new(move |s| s.b2e_auth = new_auth));
+        Callback::from(move |new_timetable: TimetableSettings| {
+            update_settings.emit(Box::new(move |s| s.timetable_settings = new_timetable));
         })
     };
 
@@ -71,7 +119,10 @@ pub fn settings() -> Html {
                 <div class="col-12 col-md-8 col-lg-6">
                     <div class="d-flex justify-content-between align-items-center mb-4">
                         <h2 class="text-white mb-0">{"Settings"}</h2>
-                        <ClearSettingsButton />
+                        <div class="d-flex gap-2">
+                            <TransferSettingsButton />
+                            <ClearSettingsButton />
+                        </div>
                     </div>
 
                     {if let Some(msg) = &*error_message {
@@ -91,11 +142,32 @@ pub fn settings() -> Html {
                             on_save={b2e_save}
                         />
 
+                        <AuthSettingsCard
+                            r#type={AuthType::Letto}
+                            initial={settings.clone().letto_auth}
+                            on_save={letto_save}
+                        />
+
                         <VisualSettingsCard
                             initial={settings.clone().visual_settings}
                             on_save={on_visual_save}
                         />
 
+                        <TimetableSettingsCard
+                            initial={settings.clone().timetable_settings}
+                            on_save={timetable_save}
+                        />
+
+                        <RefreshSettingsCard
+                            initial={settings.clone().refresh_settings}
+                            on_save={refresh_save}
+                        />
+
+                        <FeedSettingsCard
+                            initial={settings.clone().feed_settings}
+                            on_save={feed_save}
+                        />
+
                     } else {
                         <div class="alert alert-warning">
                             {"Failed to parse settings. Your settings might be corrupted. "}
@@ -0,0 +1,125 @@
+use crate::components::settings::settings_card::SettingsCard;
+use crate::persistence_manager::TimetableSettings;
+use chrono_tz::Tz;
+use web_sys::{HtmlInputElement, InputEvent, MouseEvent};
+use yew::{function_component, html, use_state, Callback, Html, Properties, TargetCast};
+
+#[derive(Properties, PartialEq)]
+pub struct TimetableCardProps {
+    pub initial: TimetableSettings,
+    pub on_save: Callback<TimetableSettings>,
+}
+
+#[function_component(TimetableSettingsCard)]
+pub fn timetable_settings_card(props: &TimetableCardProps) -> Html {
+    let crawl_all_classes = use_state(|| props.initial.crawl_all_classes);
+    let max_parallel = use_state(|| props.initial.max_parallel_requests.to_string());
+    let hidden_groups = use_state(|| props.initial.hidden_groups.clone());
+    let school_timezone = use_state(|| props.initial.school_timezone.clone());
+
+    let parsed_max_parallel = max_parallel.parse::<u32>().ok().filter(|n| (1..=20).contains(n));
+    let timezone_valid = school_timezone.trim().parse::<Tz>().is_ok();
+    let current = parsed_max_parallel.filter(|_| timezone_valid).map(|max_parallel_requests| TimetableSettings {
+        crawl_all_classes: *crawl_all_classes,
+        max_parallel_requests,
+        hidden_groups: (*hidden_groups).clone(),
+        school_timezone: school_timezone.trim().to_string(),
+    });
+    let is_dirty = current.as_ref() != Some(&props.initial);
+    let is_valid = current.is_some();
+
+    let on_toggle = {
+        let crawl_all_classes = crawl_all_classes.clone();
+        Callback::from(move |_| crawl_all_classes.set(!*crawl_all_classes))
+    };
+
+    let on_max_parallel_input = {
+        let max_parallel = max_parallel.clone();
+        Callback::from(move |e: InputEvent| {
+            max_parallel.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_timezone_input = {
+        let school_timezone = school_timezone.clone();
+        Callback::from(move |e: InputEvent| {
+            school_timezone.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_show_groups = {
+        let hidden_groups = hidden_groups.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            hidden_groups.set(Vec::new());
+        })
+    };
+
+    let handle_save = {
+        let on_save = props.on_save.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            if let Some(settings) = current.clone() {
+                on_save.emit(settings);
+            }
+        })
+    };
+
+    html! {
+        <SettingsCard title="Timetable">
+            <form>
+                <div class="mb-3 form-check">
+                    <input
+                        type="checkbox"
+                        class="form-check-input"
+                        id="crawlCheck"
+                        checked={*crawl_all_classes}
+                        onclick={on_toggle}
+                    />
+                    <label class="form-check-label small text-secondary" for="crawlCheck" style="cursor: pointer;">
+                        {"Reconstruct teacher and room timetables from all classes"}
+                    </label>
+                    <div class="form-text">
+                        {"Only needed if your account can't open teacher or room timetables. Loads every class of the school and is much slower."}
+                    </div>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label small text-secondary">{"Parallel requests"}</label>
+                    <input
+                        type="number"
+                        min="1"
+                        max="20"
+                        class={if parsed_max_parallel.is_some() { "form-control" } else { "form-control is-invalid" }}
+                        value={(*max_parallel).clone()}
+                        oninput={on_max_parallel_input}
+                    />
+                    <div class="invalid-feedback">{"Between 1 and 20"}</div>
+                    <div class="form-text">{"Lower this if your school's WebUntis throttles the app."}</div>
+                </div>
+                <div class="mb-3">
+                    <label class="form-label small text-secondary">{"School timezone"}</label>
+                    <input
+                        type="text"
+                        class={if timezone_valid { "form-control" } else { "form-control is-invalid" }}
+                        value={(*school_timezone).clone()}
+                        oninput={on_timezone_input}
+                    />
+                    <div class="invalid-feedback">{"A timezone like Europe/Vienna"}</div>
+                    <div class="form-text">{"Calendar exports use it, so lessons keep their time on devices in other timezones."}</div>
+                </div>
+                if !hidden_groups.is_empty() {
+                    <div class="mb-3">
+                        <label class="form-label small text-secondary">{"Hidden in my timetable"}</label>
+                        <div class="d-flex align-items-center gap-2">
+                            <span class="small flex-grow-1">{ hidden_groups.join(", ") }</span>
+                            <button class="btn btn-sm btn-outline-secondary" onclick={on_show_groups}>{"Show again"}</button>
+                        </div>
+                    </div>
+                }
+                <button onclick={handle_save} disabled={!is_dirty || !is_valid} class={if is_dirty {"btn btn-primary w-100"} else {"btn btn-outline-success w-100"}}>
+                    {if is_dirty {"Save Changes"} else {"Saved"}}
+                </button>
+            </form>
+        </SettingsCard>
+    }
+}
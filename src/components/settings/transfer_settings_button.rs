@@ -0,0 +1,233 @@
+use crate::components::download::download_file;
+use crate::components::qr_code::QrCode;
+use crate::qr_scanner::{can_scan, scan_qr};
+use crate::settings_transfer::{export_settings, import_settings};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Event, HtmlInputElement, HtmlTextAreaElement, InputEvent};
+use yew::{function_component, html, use_state, Callback, Html, TargetCast, UseStateHandle};
+
+// byte mode capacity of the largest qr code at the lowest error correction
+const MAX_QR_LEN: usize = 2953;
+
+#[function_component(TransferSettingsButton)]
+pub fn transfer_settings_button() -> Html {
+    let show_modal = use_state(|| false);
+    let passphrase = use_state(String::new);
+    let include_cache = use_state(|| false);
+    let exported = use_state(|| None::<String>);
+    let qr_data = use_state(|| None::<String>);
+    let import_data = use_state(String::new);
+    let busy = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    let toggle_modal = {
+        let show_modal = show_modal.clone();
+        let exported = exported.clone();
+        let qr_data = qr_data.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            exported.set(None);
+            qr_data.set(None);
+            error.set(None);
+            show_modal.set(!*show_modal)
+        })
+    };
+
+    let on_input = |state: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            state.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_paste = {
+        let import_data = import_data.clone();
+        Callback::from(move |e: InputEvent| {
+            import_data.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let on_file = {
+        let import_data = import_data.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let Some(file) = e.target_unchecked_into::<HtmlInputElement>().files().and_then(|f| f.get(0)) else {
+                return;
+            };
+            let import_data = import_data.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match JsFuture::from(file.text()).await.ok().and_then(|t| t.as_string()) {
+                    Some(text) => import_data.set(text),
+                    None => error.set(Some("The file can't be read".to_string())),
+                }
+            });
+        })
+    };
+
+    let on_export = {
+        let passphrase = passphrase.clone();
+        let include_cache = include_cache.clone();
+        let exported = exported.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let passphrase = (*passphrase).clone();
+            let include_cache = *include_cache;
+            let exported = exported.clone();
+            let busy = busy.clone();
+            let error = error.clone();
+            busy.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match export_settings(&passphrase, include_cache).await {
+                    Ok(data) => {
+                        if let Err(e) = download_file("altis-settings.txt", "text/plain", &data) {
+                            log::warn!("Settings download failed: {}", e);
+                        }
+                        exported.set(Some(data));
+                    }
+                    Err(e) => error.set(Some(format!("Export failed: {}", e))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    // never includes the cache, a qr code only fits a few kilobytes
+    let on_show_qr = {
+        let passphrase = passphrase.clone();
+        let qr_data = qr_data.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let passphrase = (*passphrase).clone();
+            let qr_data = qr_data.clone();
+            let busy = busy.clone();
+            let error = error.clone();
+            busy.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match export_settings(&passphrase, false).await {
+                    Ok(data) if data.len() > MAX_QR_LEN => {
+                        error.set(Some("The settings are too large for a QR code, use the file export".to_string()));
+                    }
+                    Ok(data) => qr_data.set(Some(data)),
+                    Err(e) => error.set(Some(format!("Export failed: {}", e))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_scan = {
+        let import_data = import_data.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let import_data = import_data.clone();
+            let error = error.clone();
+            error.set(None);
+            spawn_local(async move {
+                match scan_qr().await {
+                    Ok(data) => import_data.set(data),
+                    Err(e) => error.set(Some(format!("Scanning failed: {}", e))),
+                }
+            });
+        })
+    };
+
+    let on_import = {
+        let passphrase = passphrase.clone();
+        let import_data = import_data.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let passphrase = (*passphrase).clone();
+            let import_data = (*import_data).clone();
+            let busy = busy.clone();
+            let error = error.clone();
+            busy.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match import_settings(&import_data, &passphrase).await {
+                    Ok(()) => {
+                        let _ = web_sys::window().unwrap().location().reload();
+                    }
+                    Err(e) => {
+                        error.set(Some(e));
+                        busy.set(false);
+                    }
+                }
+            });
+        })
+    };
+
+    let on_toggle_cache = {
+        let include_cache = include_cache.clone();
+        Callback::from(move |_| include_cache.set(!*include_cache))
+    };
+
+    html! {
+        <>
+            <button class="btn btn-outline-primary" onclick={toggle_modal.clone()}>
+                {"Transfer"}
+            </button>
+
+            if *show_modal {
+                <div class="modal d-block" tabindex="-1" style="background: rgba(0,0,0,0.5)">
+                    <div class="modal-dialog modal-dialog-centered">
+                        <div class="modal-content bg-dark text-white border-primary">
+                            <div class="modal-header">
+                                <h5 class="modal-title">{"Export / Import Settings"}</h5>
+                                <button type="button" class="btn-close btn-close-white" onclick={toggle_modal.clone()}></button>
+                            </div>
+                            <div class="modal-body">
+                                { for error.as_ref().map(|msg| html! {
+                                    <div class="alert alert-danger py-2 small">{ msg }</div>
+                                }) }
+
+                                <div class="mb-3">
+                                    <label class="form-label small text-secondary">{"Passphrase"}</label>
+                                    <input type="password" class="form-control" value={(*passphrase).clone()} oninput={on_input(passphrase.clone())} />
+                                    <div class="form-text">{"Exports contain your credentials and are always encrypted with it."}</div>
+                                </div>
+
+                                <h6 class="mt-4">{"Export"}</h6>
+                                <div class="mb-2 form-check">
+                                    <input type="checkbox" class="form-check-input" id="exportCacheCheck" checked={*include_cache} onclick={on_toggle_cache} />
+                                    <label class="form-check-label small text-secondary" for="exportCacheCheck">{"Include the timetable cache"}</label>
+                                </div>
+                                <div class="d-flex gap-2">
+                                    <button type="button" class="btn btn-primary w-100" disabled={*busy || passphrase.is_empty()} onclick={on_export}>{"Export"}</button>
+                                    <button type="button" class="btn btn-outline-primary w-100" disabled={*busy || passphrase.is_empty()} onclick={on_show_qr}>
+                                        <i class="bi bi-qr-code me-2"></i>{"Show QR Code"}
+                                    </button>
+                                </div>
+                                if let Some(data) = &*exported {
+                                    <textarea class="form-control form-control-sm mt-2 font-monospace" rows="3" readonly=true value={data.clone()} />
+                                }
+                                if let Some(data) = &*qr_data {
+                                    <div class="bg-white rounded p-2 mt-2 mx-auto" style="width: 280px; height: 280px;">
+                                        <QrCode data={data.clone()} />
+                                    </div>
+                                    <p class="small text-secondary text-center mt-2 mb-0">{"Scan this on the other device under Settings → Transfer."}</p>
+                                }
+
+                                <h6 class="mt-4">{"Import"}</h6>
+                                if can_scan() {
+                                    <button type="button" class="btn btn-outline-primary w-100 mb-2" onclick={on_scan}>
+                                        <i class="bi bi-camera me-2"></i>{"Scan QR Code"}
+                                    </button>
+                                }
+                                <input type="file" class="form-control mb-2" accept=".txt,text/plain" onchange={on_file} />
+                                <textarea class="form-control form-control-sm mb-2 font-monospace" rows="3" placeholder="or paste an exported string" value={(*import_data).clone()} oninput={on_paste} />
+                                <button type="button" class="btn btn-outline-primary w-100" disabled={*busy || passphrase.is_empty() || import_data.trim().is_empty()} onclick={on_import}>
+                                    {"Import and Reload"}
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            }
+        </>
+    }
+}
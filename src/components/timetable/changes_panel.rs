@@ -0,0 +1,50 @@
+use crate::data_models::clean_models::changes::TimetableChange;
+use yew::{function_component, html, use_state, Callback, Html, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct ChangesPanelProps {
+    pub changes: Vec<TimetableChange>, // only those of the displayed entity
+    pub on_dismiss: Callback<()>,
+}
+
+#[function_component(ChangesPanel)]
+pub fn changes_panel(props: &ChangesPanelProps) -> Html {
+    let expanded = use_state(|| false);
+
+    if props.changes.is_empty() {
+        return html! {};
+    }
+
+    let on_toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |_| expanded.set(!*expanded))
+    };
+    let on_dismiss = props.on_dismiss.clone();
+
+    html! {
+        <div class="card border-warning mx-3 mt-2 shadow-sm" style="background-color: #2b3035;">
+            <div class="card-header d-flex align-items-center gap-2 py-2" style="cursor: pointer;" onclick={on_toggle}>
+                <i class="bi bi-bell-fill text-warning"></i>
+                <span class="text-white">{"What changed since your last visit"}</span>
+                <span class="badge bg-warning text-dark">{ props.changes.len() }</span>
+                <i class={if *expanded { "bi bi-chevron-up ms-auto text-secondary" } else { "bi bi-chevron-down ms-auto text-secondary" }}></i>
+            </div>
+            if *expanded {
+                <ul class="list-group list-group-flush">
+                    { for props.changes.iter().map(|c| html! {
+                        <li class="list-group-item bg-transparent text-light small d-flex gap-2">
+                            <i class={format!("bi {} text-warning", c.kind.icon())}></i>
+                            <span class="flex-grow-1">{ c.describe() }</span>
+                            <span class="text-secondary text-nowrap">{ c.detected.format("%d.%m. %H:%M").to_string() }</span>
+                        </li>
+                    })}
+                </ul>
+                <div class="card-body py-2 text-end">
+                    <button class="btn btn-sm btn-outline-warning" onclick={move |_| on_dismiss.emit(())}>
+                        {"Mark as seen"}
+                    </button>
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,124 @@
+use crate::components::loading::LoadingComponent;
+use crate::untis::untis_client::UntisClient;
+use chrono::{Datelike, Duration, Local};
+use web_sys::{HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+use yew::suspense::use_future_with;
+
+#[derive(Properties, PartialEq)]
+pub struct ExamAgendaProps {
+    pub filter: Option<String>, // class, teacher or room name
+    pub on_close: Callback<()>,
+}
+
+#[function_component(ExamAgenda)]
+pub fn exam_agenda(props: &ExamAgendaProps) -> Html {
+    let weeks = use_state(|| 4i64);
+    let on_close = props.on_close.clone();
+
+    let on_weeks_change = {
+        let weeks = weeks.clone();
+        Callback::from(move |e: Event| {
+            if let Ok(val) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                weeks.set(val);
+            }
+        })
+    };
+
+    html! {
+        <div class="modal d-block" style="background: rgba(0,0,0,0.85); z-index: 1050;" onclick={
+            let on_close = on_close.clone();
+            move |_| on_close.emit(())
+        }>
+            <div class="modal-dialog modal-lg modal-dialog-centered" onclick={|e: MouseEvent| e.stop_propagation()}>
+                <div class="modal-content border-primary shadow-lg bg-dark text-light">
+                    <div class="modal-header border-primary bg-black text-white">
+                        <h5 class="modal-title fw-bold me-3">{"Upcoming exams"}</h5>
+                        <select class="form-select form-select-sm bg-dark text-white border-0 shadow-sm w-auto select-primary-dropdown-icon" onchange={on_weeks_change}>
+                            { for [1i64, 2, 4, 8].into_iter().map(|n| html! {
+                                <option value={n.to_string()} selected={*weeks == n}>
+                                    { if n == 1 { "Next week".to_string() } else { format!("Next {} weeks", n) } }
+                                </option>
+                            })}
+                        </select>
+                        <button type="button" class="btn-close btn-close-white" onclick={
+                            let on_close = on_close.clone();
+                            move |_| on_close.emit(())
+                        }></button>
+                    </div>
+                    <div class="modal-body p-4 custom-scrollbar" style="max-height: 80vh; overflow-y: auto; background-color: #1a1d20;">
+                        <Suspense fallback={html! { <LoadingComponent /> }}>
+                            <ExamAgendaList weeks={*weeks} filter={props.filter.clone()} />
+                        </Suspense>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ExamAgendaListProps {
+    weeks: i64,
+    filter: Option<String>,
+}
+
+#[function_component(ExamAgendaList)]
+fn exam_agenda_list(props: &ExamAgendaListProps) -> HtmlResult {
+    let res = use_future_with(props.weeks, |weeks| async move {
+        let today = Local::now().date_naive();
+        UntisClient::new()?.get_exams(today, today + Duration::weeks(*weeks)).await
+    })?;
+
+    let exams = match &*res {
+        Ok(exams) => exams,
+        Err(err) => return Ok(html! { <div class="alert alert-danger">{ err.to_string() }</div> }),
+    };
+
+    let visible: Vec<_> = exams
+        .iter()
+        .filter(|e| props.filter.as_ref().is_none_or(|f| e.concerns(f)))
+        .collect();
+
+    if visible.is_empty() {
+        return Ok(html! { <p class="text-secondary mb-0">{"No upcoming exams"}</p> });
+    }
+
+    Ok(html! {
+        <>
+        { for visible.iter().map(|exam| html! {
+            <div class="card mb-3 shadow-sm border-0" style="border-left: 5px solid #fac444 !important; background-color: #2b3035;">
+                <div class="card-body text-light">
+                    <div class="d-flex align-items-center gap-2 mb-1">
+                        <span class="badge bg-warning text-dark">
+                            { if exam.exam_type.is_empty() { "Exam" } else { exam.exam_type.as_str() } }
+                        </span>
+                        <h5 class="card-title fw-bold mb-0 text-white">{ &exam.subject }</h5>
+                        <span class="text-secondary small ms-auto text-nowrap">
+                            <i class="bi bi-calendar-event me-1"></i>
+                            { format!("{} {} {} - {}",
+                                exam.time_range.start.weekday(),
+                                exam.time_range.start.format("%d.%m."),
+                                exam.time_range.start.format("%H:%M"),
+                                exam.time_range.end.format("%H:%M")) }
+                        </span>
+                    </div>
+                    if !exam.name.is_empty() {
+                        <div>{ &exam.name }</div>
+                    }
+                    <div class="small text-secondary">
+                        { [exam.classes.join(", "), exam.teachers.join(", "), exam.rooms.join(", ")]
+                            .into_iter()
+                            .filter(|s| !s.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" · ") }
+                    </div>
+                    if !exam.text.is_empty() {
+                        <div class="small text-secondary mt-1" style="white-space: pre-wrap;">{ &exam.text }</div>
+                    }
+                </div>
+            </div>
+        })}
+        </>
+    })
+}
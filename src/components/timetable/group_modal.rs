@@ -6,6 +6,9 @@ use yew::{function_component, html, Callback, Html, Properties};
 pub struct GroupModalProps {
     pub lessons: Vec<LessonBlock>,
     pub on_close: Callback<()>,
+    // only offered in the personal timetable, emits the group key of the lesson
+    #[prop_or_default]
+    pub on_hide: Option<Callback<String>>,
 }
 
 #[function_component(GroupDetailModal)]
@@ -29,6 +32,13 @@ pub fn group_detail_modal(props: &GroupModalProps) -> Html {
                     <div class="modal-body p-4 custom-scrollbar" style="max-height: 80vh; overflow-y: auto; background-color: #1a1d20;">
                         { for props.lessons.iter().filter(|l| l.r#type != "Break").map(|l| {
                             let border_style = format!("border-left: 5px solid #{} !important; background-color: #2b3035;", l.color_hex);
+                            let hide = props.on_hide.clone().zip(l.group_key()).map(|(on_hide, key)| {
+                                let on_close = on_close.clone();
+                                Callback::from(move |_: MouseEvent| {
+                                    on_hide.emit(key.clone());
+                                    on_close.emit(());
+                                })
+                            });
                             html! {
                                 <div class="card mb-3 shadow-sm border-0" style={border_style}>
                                     <div class="card-body text-light">
@@ -55,6 +65,12 @@ pub fn group_detail_modal(props: &GroupModalProps) -> Html {
                                                         Entity::Room(_) => ("bg-warning text-dark", "bi-geo-alt"),
                                                         Entity::Subject(_) => ("bg-info text-dark", "bi-book"),
                                                         Entity::Info(_) => ("bg-secondary", "bi-info-circle"),
+                                                        Entity::Student(_) => ("bg-light", "bi-person"),
+                                                    };
+                                                    let long_name = entity.inner.long_name();
+                                                    let capacity = match &entity.inner {
+                                                        Entity::Room(r) => r.capacity,
+                                                        _ => None,
                                                     };
 
                                                     html! {
@@ -62,6 +78,12 @@ pub fn group_detail_modal(props: &GroupModalProps) -> Html {
                                                              style="width: fit-content; font-size: 0.85rem; min-width: max-content;">
                                                             <i class={format!("bi {} me-2", icon)}></i>
                                                             <strong style="letter-spacing: 0.3px;">{ entity.inner.name() }</strong>
+                                                            if long_name != entity.inner.name() {
+                                                                <span class="ms-1 opacity-75">{ long_name }</span>
+                                                            }
+                                                            if let Some(capacity) = capacity {
+                                                                <span class="ms-2 opacity-75" title="Seats"><i class="bi bi-person me-1"></i>{ capacity }</span>
+                                                            }
                                                         </div>
                                                     }
                                                 })}
@@ -95,6 +117,51 @@ pub fn group_detail_modal(props: &GroupModalProps) -> Html {
                                             }
                                         })}
 
+                                        if let Some(exam) = &l.exam {
+                                            <div class="mt-2 pt-2 border-top border-secondary">
+                                                <span class="badge bg-warning text-dark mb-1">
+                                                    <i class="bi bi-pencil-square me-1"></i>{ if exam.exam_type.is_empty() { "Exam" } else { exam.exam_type.as_str() } }
+                                                </span>
+                                                if !exam.name.is_empty() {
+                                                    <div class="small"><strong class="text-light">{ &exam.name }</strong></div>
+                                                }
+                                                <div class="small text-secondary">
+                                                    { format!("{} - {}", exam.time_range.start.format("%H:%M"), exam.time_range.end.format("%H:%M")) }
+                                                    if !exam.rooms.is_empty() {
+                                                        { format!(" · {}", exam.rooms.join(", ")) }
+                                                    }
+                                                </div>
+                                                if !exam.text.is_empty() {
+                                                    <div class="small text-secondary">{ &exam.text }</div>
+                                                }
+                                            </div>
+                                        }
+
+                                        if !l.homework.is_empty() {
+                                            <div class="mt-2 pt-2 border-top border-secondary">
+                                                { for l.homework.iter().map(|h| html! {
+                                                    <div class="small text-secondary mb-1">
+                                                        <strong class="text-light">
+                                                            <i class="bi bi-journal-check me-1"></i>
+                                                            { format!("Homework due {}: ", h.due.format("%d.%m.")) }
+                                                        </strong>
+                                                        { &h.text }
+                                                        if !h.remark.is_empty() {
+                                                            <div class="fst-italic">{ &h.remark }</div>
+                                                        }
+                                                    </div>
+                                                })}
+                                            </div>
+                                        }
+
+                                        if let Some(hide) = hide {
+                                            <div class="mt-3">
+                                                <button type="button" class="btn btn-sm btn-outline-secondary p-1 px-2" onclick={hide}>
+                                                    <i class="bi bi-eye-slash me-1"></i>{"Not my group"}
+                                                </button>
+                                            </div>
+                                        }
+
                                         if !l.link.is_empty() {
                                             <div class="mt-3">
                                                 <a href={l.link.clone()} target="_blank" rel="noopener noreferrer" class="btn btn-sm btn-outline-info p-1 px-2 text-decoration-none">
@@ -0,0 +1,65 @@
+use crate::data_models::clean_models::untis::Homework;
+use chrono::Datelike;
+use web_sys::MouseEvent;
+use yew::{function_component, html, Callback, Html, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct HomeworkListProps {
+    pub homework: Vec<Homework>, // sorted by due date
+    pub on_close: Callback<()>,
+}
+
+#[function_component(HomeworkList)]
+pub fn homework_list(props: &HomeworkListProps) -> Html {
+    let on_close = props.on_close.clone();
+
+    html! {
+        <div class="modal d-block" style="background: rgba(0,0,0,0.85); z-index: 1050;" onclick={
+            let on_close = on_close.clone();
+            move |_| on_close.emit(())
+        }>
+            <div class="modal-dialog modal-lg modal-dialog-centered" onclick={|e: MouseEvent| e.stop_propagation()}>
+                <div class="modal-content border-primary shadow-lg bg-dark text-light">
+                    <div class="modal-header border-primary bg-black text-white">
+                        <h5 class="modal-title fw-bold">{"Due this week"}</h5>
+                        <button type="button" class="btn-close btn-close-white" onclick={
+                            let on_close = on_close.clone();
+                            move |_| on_close.emit(())
+                        }></button>
+                    </div>
+                    <div class="modal-body p-4 custom-scrollbar" style="max-height: 80vh; overflow-y: auto; background-color: #1a1d20;">
+                        if props.homework.is_empty() {
+                            <p class="text-secondary mb-0">{"No homework due this week"}</p>
+                        }
+                        { for props.homework.iter().map(|h| html! {
+                            <div class="card mb-3 shadow-sm border-0" style="border-left: 5px solid #fac444 !important; background-color: #2b3035;">
+                                <div class="card-body text-light">
+                                    <div class="d-flex align-items-center gap-2 mb-2">
+                                        <h5 class="card-title fw-bold mb-0 text-white">{ &h.subject }</h5>
+                                        if h.completed {
+                                            <span class="badge bg-success">{"Done"}</span>
+                                        }
+                                        <span class="text-secondary small ms-auto">
+                                            <i class="bi bi-calendar-event me-1"></i>
+                                            { format!("{} {}", h.due.weekday(), h.due.format("%d.%m.")) }
+                                        </span>
+                                    </div>
+                                    <div style="white-space: pre-wrap;">{ &h.text }</div>
+                                    if !h.remark.is_empty() {
+                                        <div class="small text-secondary fst-italic mt-1">{ &h.remark }</div>
+                                    }
+                                    <div class="small text-secondary mt-2">
+                                        { format!("Given {}", h.assigned.format("%d.%m.")) }
+                                        if !h.teacher.is_empty() {
+                                            { format!(" by {}", h.teacher) }
+                                        }
+                                    </div>
+                                </div>
+                            </div>
+                        })}
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -20,7 +20,7 @@ pub fn generate_lessons_html(
         on_group_click.emit(lessons_to_emit.clone());
     });
 
-    let priority = lessons.iter().find(|l| l.r#type == "EXAM")
+    let priority = lessons.iter().find(|l| l.is_exam())
         .or_else(|| lessons.iter().find(|l| l.status == "CHANGED"))
         .or_else(|| lessons.iter().find(|l| l.status == "ADDITIONAL"))
         .or_else(|| lessons.iter().find(|l| l.status != "CANCELLED"))
@@ -87,14 +87,25 @@ fn render_lesson(lesson: &LessonBlock, group_duration: f64, group_start: NaiveDa
         },
         "CHANGED" => cls += " border border-4 border-info",
         "ADDITIONAL" => cls += " border border-4 border-success",
-        _ if lesson.r#type == "EXAM" => cls += " border border-4 border-warning",
+        _ if lesson.is_exam() => {
+            cls += " border border-4 border-warning";
+            style += "background-image: repeating-linear-gradient(45deg, transparent, transparent 10px, rgba(250,196,68,0.35) 10px, rgba(250,196,68,0.35) 20px);";
+        },
         _ => {}
     }
 
     html! {
         <div style={format!("position: absolute; top: {top}%; left: {x_offset}%; height: {h}%; width: {width}%; padding: {};", if is_mobile { "0" } else { "0.2rem" })} class="lesson-container">
             <div class={cls} style={style}>
+                if !lesson.homework.is_empty() {
+                    <i class="bi bi-journal-check position-absolute" style="top: 0.3rem; right: 0.5rem; font-size: 0.8rem;"></i>
+                }
                 <div class="dynamic-text">
+                    if let Some(exam) = &lesson.exam {
+                        <span class="badge bg-warning text-dark mb-1">
+                            <i class="bi bi-pencil-square me-1"></i>{ if exam.exam_type.is_empty() { "Exam" } else { exam.exam_type.as_str() } }
+                        </span><br/>
+                    }
                     { render_entity(lesson, |e| matches!(e, Entity::Subject(..))) } <br/>
                     { render_entity(lesson, |e| matches!(e, Entity::Teacher(..))) } <br/>
                     { render_entity(lesson, |e| matches!(e, Entity::Room(..))) }
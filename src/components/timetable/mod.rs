@@ -3,3 +3,6 @@ pub mod timetable_render;
 mod timetable_controls;
 mod lessons_render_helper;
 mod group_modal;
+mod homework_list;
+mod exam_agenda;
+mod changes_panel;
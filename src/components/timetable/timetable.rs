@@ -1,56 +1,336 @@
+use crate::components::app::DataVersion;
+use crate::components::download::download_file;
+use crate::components::timetable::changes_panel::ChangesPanel;
+use crate::components::timetable::exam_agenda::ExamAgenda;
+use crate::components::timetable::homework_list::HomeworkList;
 use crate::components::timetable::timetable_controls::TimetableControls;
 use crate::components::timetable::timetable_render::TimeTableRender;
-use crate::data_models::clean_models::untis::{Entity, WeekTimeTable};
+use crate::data_models::clean_models::untis::{Class, Entity, Resource, ResourceType, WeekTimeTable};
+use crate::errors::ApiError;
+use crate::persistence_manager::PersistenceManager;
 use crate::untis::cached_untis_client::CachedUntisClient;
+use crate::untis::teacher_table_generator::gen_all_timetables;
+use crate::untis::untis_client::CrawlProgress;
 use crate::untis::untis_week::Week;
+use chrono::NaiveDateTime;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use yew::suspense::use_future_with;
 
+// the crawl loads every timetable of the week, otherwise only the selected one is fetched
+enum Loaded {
+    All {
+        tables: HashMap<Entity, WeekTimeTable>,
+        own_class: Option<i32>,
+    },
+    // the week isn't cached yet and is being crawled, see Crawl
+    Crawling,
+    Direct {
+        resources: Vec<Resource>,
+        active: Option<Box<(Resource, WeekTimeTable)>>, // boxed, a resource is much larger than the other variants
+    },
+}
+
+type LoadResult = Result<(Loaded, Option<NaiveDateTime>), ApiError>;
+
+// what arrived so far while a week is crawled
+#[derive(Default)]
+struct Crawl {
+    loaded: usize,
+    total: usize,
+    own_class: Option<i32>,
+    tables: HashMap<Class, WeekTimeTable>,
+    error: Option<String>,
+}
+
+// loads the selected week, the whole crawl or just the selected timetable
+async fn load(
+    week: Week,
+    direct: Option<(String, Option<String>)>,
+    crawls: Rc<RefCell<HashMap<Week, Crawl>>>,
+    category_handle: UseStateHandle<String>,
+    on_revalidated: Callback<()>,
+    on_crawl_progress: Callback<(Week, Result<CrawlProgress, String>)>,
+) -> LoadResult {
+    let client = CachedUntisClient::new()?.with_revalidation_callback(on_revalidated);
+
+    let Some((category, name)) = &direct else {
+        let Some((tables, own_class)) = client.get_all_timetables_progressive(week.clone(), on_crawl_progress).await? else {
+            // a failed crawl was started again
+            if let Some(crawl) = crawls.borrow_mut().get_mut(&week) {
+                crawl.error = None;
+            }
+            return Ok((Loaded::Crawling, None));
+        };
+        crawls.borrow_mut().remove(&week);
+        return Ok((Loaded::All { tables, own_class }, CachedUntisClient::last_updated(&week).await));
+    };
+
+    let kind = match category.as_str() {
+        "Teacher" => ResourceType::Teacher,
+        "Room" => ResourceType::Room,
+        "Me" => ResourceType::Student,
+        _ => ResourceType::Class,
+    };
+    let resources = match client.get_resources(kind).await {
+        // e.g. teacher accounts, they start with the class view instead
+        Ok(resources) if kind == ResourceType::Student && resources.is_empty() => {
+            log::info!("This account has no personal timetable");
+            category_handle.set("Class".to_string());
+            return Ok((Loaded::Direct { resources, active: None }, None));
+        }
+        Err(ApiError::Permission(e)) if kind == ResourceType::Student => {
+            log::info!("No personal timetable: {}", e);
+            category_handle.set("Class".to_string());
+            return Ok((Loaded::Direct { resources: Vec::new(), active: None }, None));
+        }
+        resources => resources?,
+    };
+    // only picks the initial selection, not knowing it mustn't hide a cached timetable
+    let own_class = client.own_class().await.unwrap_or_else(|e| {
+        log::warn!("Failed to get the own class: {}", e);
+        None
+    });
+    let selected = resources
+        .iter()
+        .find(|r| Some(r.entity.name()) == *name)
+        .or_else(|| resources.iter().find(|r| kind == ResourceType::Class && Some(r.id) == own_class))
+        .or(resources.first())
+        .cloned();
+
+    let (active, last_updated) = match selected {
+        Some(resource) => {
+            let table = client.get_resource_timetable(week.clone(), &resource).await?;
+            let last_updated = CachedUntisClient::table_last_updated(&week, &resource).await;
+            (Some(Box::new((resource, table))), last_updated)
+        }
+        None => (None, None),
+    };
+    Ok((Loaded::Direct { resources, active }, last_updated))
+}
+
 #[function_component(TimetableComponent)]
 pub fn timetable() -> HtmlResult {
     let reload_trigger = use_state(|| 0);
-    let category = use_state(|| "Class".to_string());
+    let category = use_state(|| "Me".to_string());
     let selected_name = use_state(|| None::<String>);
     let selected_week = use_state(Week::current);
     let pointer_start_x = use_state(|| 0.0);
     let current_offset = use_state(|| 0.0);
     let is_dragging = use_state(|| false);
+    let show_homework = use_state(|| false);
+    let show_exams = use_state(|| false);
+    let export_error = use_state(|| None::<String>);
+    let force_update = use_force_update();
+    let revalidations = use_state(|| 0u32);
+    let revalidation_count = use_mut_ref(|| 0u32);
+    let crawls = use_mut_ref(HashMap::<Week, Crawl>::new);
+    let crawl_updates = use_state(|| 0u32);
+    let crawl_update_count = use_mut_ref(|| 0u32);
+
+    // background refreshes only reload from the cache, the selection stays as it is
+    let data_version = use_context::<DataVersion>().unwrap_or_default();
+
+    // stale weeks are shown right away, the revalidated data arrives through this callback
+    let on_revalidated = {
+        let revalidations = revalidations.clone();
+        Callback::from(move |_| {
+            *revalidation_count.borrow_mut() += 1;
+            revalidations.set(*revalidation_count.borrow());
+        })
+    };
+
+    // every class of an uncached week as it arrives, re-renders without refetching anything
+    let on_crawl_progress = {
+        let crawls = crawls.clone();
+        let crawl_updates = crawl_updates.clone();
+        Callback::from(move |(week, update): (Week, Result<CrawlProgress, String>)| {
+            {
+                let mut crawls = crawls.borrow_mut();
+                let crawl = crawls.entry(week).or_default();
+                match update {
+                    Ok(progress) => {
+                        crawl.loaded = progress.loaded;
+                        crawl.total = progress.total;
+                        crawl.own_class = progress.own_class;
+                        if let Some(table) = progress.table {
+                            crawl.tables.insert(progress.class, table);
+                        }
+                    }
+                    Err(e) => crawl.error = Some(e),
+                }
+            }
+            *crawl_update_count.borrow_mut() += 1;
+            crawl_updates.set(*crawl_update_count.borrow());
+        })
+    };
+
+    // without the crawl a different selection means a different request, the personal timetable is never crawled
+    let direct = (*category == "Me" || !CachedUntisClient::crawls_all_classes())
+        .then(|| ((*category).clone(), (*selected_name).clone()));
+    let selection = (*reload_trigger, direct);
+
+    // background refreshes and revalidations reload in place, the shown week stays until the new data is there.
+    // only a different selection suspends
+    let refreshed = use_state(|| None::<((u32, Option<(String, Option<String>)>), Rc<LoadResult>)>);
+    {
+        let refreshed = refreshed.clone();
+        use_effect_with(selection.clone(), move |_| {
+            if refreshed.is_some() {
+                refreshed.set(None);
+            }
+            || ()
+        });
+    }
+    {
+        let refreshed = refreshed.clone();
+        let selection = selection.clone();
+        let crawls = crawls.clone();
+        let category = category.clone();
+        let selected_week = (*selected_week).clone();
+        let on_revalidated = on_revalidated.clone();
+        let on_crawl_progress = on_crawl_progress.clone();
+        let mounted = use_mut_ref(|| false);
+        use_effect_with((data_version, *revalidations), move |_| {
+            // the first load is the suspending one below
+            if std::mem::replace(&mut *mounted.borrow_mut(), true) {
+                spawn_local(async move {
+                    let result = load(selected_week, selection.1.clone(), crawls, category, on_revalidated, on_crawl_progress).await;
+                    refreshed.set(Some((selection, Rc::new(result))));
+                });
+            }
+            || ()
+        });
+    }
 
     let res = {
-        let trigger = *reload_trigger;
+        let crawls = crawls.clone();
+        let category_handle = category.clone();
         let selected_week = selected_week.clone();
-        use_future_with(trigger, |_| async move {
-            CachedUntisClient::new()?.get_all_timetables((*selected_week).clone()).await
+        use_future_with(selection.clone(), |deps| {
+            load((*selected_week).clone(), deps.1.clone(), crawls, category_handle, on_revalidated, on_crawl_progress)
         })?
     };
+    let res: &LoadResult = match &*refreshed {
+        Some((refreshed_for, result)) if *refreshed_for == selection => result.as_ref(),
+        _ => &res,
+    };
 
-    match &*res {
+    match res {
         Err(err) => Ok(html! { <div class="alert alert-danger m-3">{ err.to_string() }</div> }),
-        Ok((map, initial_id)) => {
-            if selected_name.is_none()
-                && let Some(id) = initial_id {
-                    let initial = map.keys().find(|e| {
-                        if let Entity::Class(c) = e { c.id == *id } else { false }
-                    }).map(|e| e.name());
-                    selected_name.set(initial);
+        Ok((loaded, last_updated)) => {
+            let last_updated = *last_updated;
+            // picks the selection from a whole week, while crawling the own class is waited for instead of showing another
+            let pick = |map: &HashMap<Entity, WeekTimeTable>, initial_id: Option<i32>, fallback_first: bool| {
+                if selected_name.is_none()
+                    && let Some(id) = initial_id {
+                        let initial = map.keys().find(|e| {
+                            if let Entity::Class(c) = e { c.id == id } else { false }
+                        }).map(|e| e.name());
+                        selected_name.set(initial);
+                    }
+
+                let filtered_data: Vec<(&Entity, &WeekTimeTable)> = map.iter()
+                    .filter(|(entity, _)| match (category.as_str(), entity) {
+                        ("Class", Entity::Class(_)) => true,
+                        ("Teacher", Entity::Teacher(_)) => true,
+                        ("Room", Entity::Room(_)) => true,
+                        _ => false,
+                    })
+                    .collect();
+
+                let names: Vec<String> = filtered_data.iter().map(|(e, _)| e.name()).collect();
+                let active = filtered_data.iter()
+                    .find(|(e, _)| Some(e.name()) == *selected_name)
+                    .or(filtered_data.first().filter(|_| fallback_first))
+                    .map(|(e, t)| ((*e).clone(), (*t).clone()));
+                (names, active)
+            };
+
+            let crawl_progress = matches!(loaded, Loaded::Crawling).then(|| {
+                crawls.borrow().get(&*selected_week).map(|c| (c.loaded, c.total, c.error.clone())).unwrap_or_default()
+            });
+
+            let (mut names, active_resource, active) = match loaded {
+                Loaded::All { tables, own_class } => {
+                    let (names, active) = pick(tables, *own_class, true);
+                    (names, None, active)
                 }
+                Loaded::Crawling => {
+                    // teachers and rooms fill in as more classes arrive
+                    let derived = crawls.borrow().get(&*selected_week).and_then(|c| {
+                        gen_all_timetables(c.tables.clone(), c.own_class)
+                            .inspect_err(|e| log::warn!("Failed to derive timetables: {}", e))
+                            .ok()
+                    });
+                    match derived {
+                        Some((map, own_class)) => {
+                            let (names, active) = pick(&map, own_class, category.as_str() != "Class" || (own_class.is_none() && selected_name.is_none()));
+                            (names, None, active)
+                        }
+                        None => (Vec::new(), None, None),
+                    }
+                }
+                Loaded::Direct { resources, active } => (
+                    resources.iter().map(|r| r.entity.name()).collect(),
+                    active.as_ref().map(|a| a.0.clone()),
+                    active.as_ref().map(|a| (a.0.entity.clone(), a.1.clone())),
+                ),
+            };
+            names.sort();
+
+            let active_name = active.as_ref().map(|(e, _)| e.name());
+            let active_entity = active.as_ref().map(|(e, _)| e.clone());
+            let mut active_timetable = active.map(|(_, t)| t);
 
-            let filtered_data: Vec<(&Entity, &WeekTimeTable)> = map.iter()
-                .filter(|(entity, _)| match (category.as_str(), entity) {
-                    ("Class", Entity::Class(_)) => true,
-                    ("Teacher", Entity::Teacher(_)) => true,
-                    ("Room", Entity::Room(_)) => true,
-                    _ => false,
+            // courses of groups the student isn't in, hidden through the lesson details
+            let personal = *category == "Me";
+            if personal
+                && let Some(table) = active_timetable.as_mut()
+                && let Ok(Some(settings)) = PersistenceManager::get_settings() {
+                table.hide_groups(&settings.timetable_settings.hidden_groups);
+            }
+
+            let on_hide_group = personal.then(|| {
+                let force_update = force_update.clone();
+                Callback::from(move |key: String| {
+                    let mut settings = PersistenceManager::get_settings().ok().flatten().unwrap_or_default();
+                    if !settings.timetable_settings.hidden_groups.contains(&key) {
+                        settings.timetable_settings.hidden_groups.push(key);
+                    }
+                    if let Err(e) = PersistenceManager::save_settings(&settings) {
+                        log::warn!("Failed to hide the group: {}", e);
+                    }
+                    force_update.force_update();
                 })
+            });
+
+            let changes: Vec<_> = PersistenceManager::get_changes()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| Some(&c.entity) == active_entity.as_ref())
                 .collect();
 
-            let mut names: Vec<String> = filtered_data.iter().map(|(e, _)| e.name()).collect();
-            names.sort();
+            let on_changes_dismiss = {
+                let active_entity = active_entity.clone();
+                Callback::from(move |_| {
+                    let mut stored = PersistenceManager::get_changes().unwrap_or_default();
+                    stored.retain(|c| Some(&c.entity) != active_entity.as_ref());
+                    let _ = PersistenceManager::save_changes(&stored);
+                    force_update.force_update();
+                })
+            };
 
-            let active_timetable = filtered_data.iter()
-                .find(|(e, _)| Some(e.name()) == *selected_name)
-                .or(filtered_data.first())
-                .map(|(_, t)| (*t).clone());
+            let warnings = CachedUntisClient::warnings(&selected_week);
+
+            let homework = active_timetable
+                .as_ref()
+                .map(|t| t.homework_due(selected_week.start_date(), selected_week.end_date()))
+                .unwrap_or_default();
+            let homework_count = homework.len();
 
             let on_category_change = {
                 let category = category.clone();
@@ -65,9 +345,47 @@ pub fn timetable() -> HtmlResult {
                 let trigger = reload_trigger.clone();
                 let selected_name = selected_name.clone();
                 Callback::from(move |_| {
-                    let _ = CachedUntisClient::clear_cache();
-                    selected_name.set(None);
-                    trigger.set(*trigger + 1);
+                    let selected_name = selected_name.clone();
+                    let trigger = trigger.clone();
+                    spawn_local(async move {
+                        if let Err(e) = CachedUntisClient::invalidate_cache().await {
+                            log::warn!("Failed to invalidate the timetable cache: {}", e);
+                        }
+                        selected_name.set(None);
+                        trigger.set(*trigger + 1);
+                    });
+                })
+            };
+
+            let on_homework_toggle = {
+                let show_homework = show_homework.clone();
+                Callback::from(move |_| show_homework.set(!*show_homework))
+            };
+
+            let on_exams_toggle = {
+                let show_exams = show_exams.clone();
+                Callback::from(move |_| show_exams.set(!*show_exams))
+            };
+
+            // exports the selected week and the three after it
+            let on_export = {
+                let export_error = export_error.clone();
+                let selected_week = selected_week.clone();
+                Callback::from(move |_| {
+                    let Some(entity) = active_entity.clone() else { return };
+                    let export_error = export_error.clone();
+                    let weeks: Vec<Week> = std::iter::successors(Some((*selected_week).clone()), |w| Some(w.next()))
+                        .take(4)
+                        .collect();
+                    let resource = active_resource.clone();
+                    spawn_local(async move {
+                        let result = match (CachedUntisClient::new(), resource) {
+                            (Ok(client), Some(resource)) => client.export_resource_ics(&resource, weeks).await.map_err(|e| e.to_string()),
+                            (Ok(client), None) => client.export_ics(&entity, weeks).await.map_err(|e| e.to_string()),
+                            (Err(e), _) => Err(e.to_string()),
+                        }.and_then(|ics| download_file(&format!("{}.ics", entity.name()), "text/calendar", &ics));
+                        export_error.set(result.err());
+                    });
                 })
             };
 
@@ -154,14 +472,51 @@ pub fn timetable() -> HtmlResult {
                 <div class="d-flex flex-column flex-grow-1 h-100">
                     <TimetableControls
                         category={(*category).clone()}
-                        selected_name={(*selected_name).clone()}
+                        selected_name={active_name.clone()}
                         selected_week={(*selected_week).clone()}
+                        last_updated={last_updated}
                         filtered_names={names}
                         on_category_change={on_category_change}
                         on_entity_change={on_entity_change}
                         on_week_change={on_week_change}
                         on_reload={on_reload}
+                        homework_count={homework_count}
+                        on_homework={on_homework_toggle.clone()}
+                        on_exams={on_exams_toggle.clone()}
+                        on_export={on_export}
                     />
+                    if let Some(err) = &*export_error {
+                        <div class="alert alert-danger m-3">{ format!("Export failed: {}", err) }</div>
+                    }
+                    if let Some((loaded, total, error)) = &crawl_progress {
+                        if let Some(err) = error {
+                            <div class="alert alert-danger m-3">{ err }</div>
+                        } else {
+                            <div class="px-3 pt-2">
+                                <div class="progress" style="height: 4px;">
+                                    <div class="progress-bar" style={format!("width: {}%;", if *total == 0 { 0 } else { loaded * 100 / total })}></div>
+                                </div>
+                                <small class="text-secondary">
+                                    { if *total == 0 { "Fetching classes...".to_string() } else { format!("{}/{} classes loaded", loaded, total) } }
+                                </small>
+                            </div>
+                        }
+                    }
+                    if !warnings.is_empty() {
+                        <div class="alert alert-warning m-3 small">
+                            { format!("{} of the classes couldn't be loaded, some lessons may be missing:", warnings.len()) }
+                            <ul class="mb-0">
+                                { for warnings.iter().map(|w| html! { <li>{ w }</li> }) }
+                            </ul>
+                        </div>
+                    }
+                    <ChangesPanel changes={changes} on_dismiss={on_changes_dismiss} />
+                    if *show_homework {
+                        <HomeworkList homework={homework} on_close={on_homework_toggle} />
+                    }
+                    if *show_exams {
+                        <ExamAgenda filter={active_name} on_close={on_exams_toggle} />
+                    }
                     <div
                         onpointerdown={on_pointer_down}
                         onpointermove={on_pointer_move}
@@ -173,7 +528,10 @@ pub fn timetable() -> HtmlResult {
                         if let Some(tt) = active_timetable {
                             <TimeTableRender
                                 timetable={tt}
+                                on_hide_group={on_hide_group}
                             />
+                        } else if crawl_progress.is_some() {
+                            <p class="text-secondary m-3">{"Waiting for the selected timetable..."}</p>
                         } else {
                             <p class="text-light"> {"No selection made"} </p>
                         }
@@ -1,5 +1,5 @@
 use crate::untis::untis_week::Week;
-use chrono::NaiveDate;
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
@@ -8,11 +8,16 @@ pub struct ControlsProps {
     pub category: String,
     pub selected_name: Option<String>,
     pub selected_week: Week,
+    pub last_updated: Option<NaiveDateTime>,
     pub filtered_names: Vec<String>,
     pub on_category_change: Callback<String>,
     pub on_entity_change: Callback<String>,
     pub on_week_change: Callback<Week>,
     pub on_reload: Callback<()>,
+    pub homework_count: usize,
+    pub on_homework: Callback<()>,
+    pub on_exams: Callback<()>,
+    pub on_export: Callback<()>,
 }
 
 #[function_component(TimetableControls)]
@@ -21,6 +26,9 @@ pub fn timetable_controls(props: &ControlsProps) -> Html {
     let filtered_names = props.filtered_names.clone();
     let selected_name = props.selected_name.clone();
     let on_reload = props.on_reload.clone();
+    let on_homework = props.on_homework.clone();
+    let on_exams = props.on_exams.clone();
+    let on_export = props.on_export.clone();
 
     let on_cat_change = {
         let cb = props.on_category_change.clone();
@@ -68,20 +76,24 @@ pub fn timetable_controls(props: &ControlsProps) -> Html {
         <div class="sticky-top p-3 mb-1 shadow-lg" style="background-color: #1e1e1e; border-bottom: 1px solid #1f2227;">
             <div class="d-flex align-items-center">
                 <select class="form-select form-select-sm-md bg-dark text-white border-0 shadow-sm w-auto me-2 select-primary-dropdown-icon" onchange={on_cat_change}>
+                    <option value="Me" selected={category == "Me"}>{"My timetable"}</option>
                     <option value="Class" selected={category == "Class"}>{"Class"}</option>
                     <option value="Teacher" selected={category == "Teacher"}>{"Teacher"}</option>
                     <option value="Room" selected={category == "Room"}>{"Room"}</option>
                 </select>
 
-                <select class="form-select form-select-sm-md bg-dark text-white border-0 shadow-sm w-auto me-2 select-primary-dropdown-icon" onchange={on_ent_change}>
-                    {for filtered_names.iter().map(|name| {
-                        html! {
-                            <option value={name.clone()} selected={selected_name.as_ref() == Some(name)}>
-                                { name }
-                            </option>
-                        }
-                    })}
-                </select>
+                // there is only one personal timetable to pick
+                if category != "Me" {
+                    <select class="form-select form-select-sm-md bg-dark text-white border-0 shadow-sm w-auto me-2 select-primary-dropdown-icon" onchange={on_ent_change}>
+                        {for filtered_names.iter().map(|name| {
+                            html! {
+                                <option value={name.clone()} selected={selected_name.as_ref() == Some(name)}>
+                                    { name }
+                                </option>
+                            }
+                        })}
+                    </select>
+                }
 
                 // DESKTOP DATE SELECTOR
                 <div class="btn-group shadow-sm ms-md-2 d-none d-md-inline-flex" role="group">
@@ -122,11 +134,48 @@ pub fn timetable_controls(props: &ControlsProps) -> Html {
                     </div>
                 </div>
 
-                <button class="btn btn-outline-primary ms-auto" onclick={let on_reload = on_reload.clone(); move |_| on_reload.emit(())}>
+                if let Some(updated) = props.last_updated {
+                    <span class="small text-secondary ms-auto me-3 text-nowrap" title={updated.format("%d.%m.%Y %H:%M").to_string()}>
+                        <i class="bi bi-clock-history me-sm-1"></i>
+                        <span class="d-none d-sm-inline">{ format_age(updated) }</span>
+                    </span>
+                }
+
+                <button class={classes!("btn", "btn-outline-primary", "me-2", props.last_updated.is_none().then_some("ms-auto"))} title="Export as iCalendar" onclick={move |_| on_export.emit(())}>
+                    <i class="bi bi-calendar-plus me-sm-1"></i>
+                    <span class="d-none d-sm-inline">{"Export"}</span>
+                </button>
+
+                <button class="btn btn-outline-primary me-2" onclick={move |_| on_exams.emit(())}>
+                    <i class="bi bi-pencil-square me-sm-1"></i>
+                    <span class="d-none d-sm-inline">{"Exams"}</span>
+                </button>
+
+                <button class="btn btn-outline-primary me-2 position-relative" onclick={move |_| on_homework.emit(())}>
+                    <i class="bi bi-journal-check me-sm-1"></i>
+                    <span class="d-none d-sm-inline">{"Homework"}</span>
+                    if props.homework_count > 0 {
+                        <span class="position-absolute top-0 start-100 translate-middle badge rounded-pill bg-danger">
+                            { props.homework_count }
+                        </span>
+                    }
+                </button>
+
+                <button class="btn btn-outline-primary" onclick={let on_reload = on_reload.clone(); move |_| on_reload.emit(())}>
                     <i class="bi bi-arrow-clockwise me-sm-1"></i>
                     <span class="d-none d-sm-inline">{"Reload"}</span>
                 </button>
             </div>
         </div>
     }
-}
\ No newline at end of file
+}
+
+fn format_age(updated: NaiveDateTime) -> String {
+    let minutes = (Local::now().naive_local() - updated).num_minutes();
+    match minutes {
+        ..1 => "Updated just now".to_string(),
+        1..60 => format!("Updated {} min ago", minutes),
+        60..1440 => format!("Updated {} h ago", minutes / 60),
+        _ => format!("Updated {}", updated.format("%d.%m. %H:%M")),
+    }
+}
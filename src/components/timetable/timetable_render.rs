@@ -1,13 +1,15 @@
 use crate::components::timetable::group_modal::GroupDetailModal;
 use crate::components::timetable::lessons_render_helper::generate_lessons_html;
-use crate::data_models::clean_models::untis::{DayTimeTable, LessonBlock, TimeRange, WeekTimeTable};
+use crate::data_models::clean_models::untis::{DayNote, DayNoteKind, DayTimeTable, LessonBlock, TimeRange, WeekTimeTable};
 use crate::persistence_manager::PersistenceManager;
 use chrono::{Datelike, NaiveTime};
-use yew::{function_component, html, use_state, Callback, Html, Properties};
+use yew::{classes, function_component, html, use_state, Callback, Html, Properties};
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct TimeTableRenderProps {
     pub timetable: WeekTimeTable,
+    #[prop_or_default]
+    pub on_hide_group: Option<Callback<String>>,
 }
 
 #[function_component(TimeTableRender)]
@@ -42,9 +44,28 @@ pub fn time_table_render(props: &TimeTableRenderProps) -> Html {
     let mut days: Vec<DayTimeTable> = props.timetable.days.clone();
     days.sort_by_key(|x| x.date);
     let lessons: Vec<LessonBlock> = days.iter().flat_map(|dtt| dtt.lessons.clone()).collect();
+
+    let notes_row = if days.iter().any(|d| !d.notes.is_empty()) {
+        html! {
+            <div class="d-flex w-100 bg-dark border-bottom">
+                <div style="width: 60px;" class="flex-shrink-0"></div>
+                <div class="d-flex flex-grow-1">
+                    { for days.iter().map(|day| html! {
+                        <div class="flex-grow-1 border-start p-1" style="flex-basis: 0; min-width: 0;">
+                            { for day.notes.iter().map(render_note) }
+                        </div>
+                    })}
+                </div>
+            </div>
+        }
+    } else {
+        html! {}
+    };
+
     if lessons.is_empty() {
         return html! {
             <div class="d-flex flex-grow-1 flex-column">
+                { notes_row }
                 {"No lessons!"}
             </div>
         };
@@ -74,7 +95,7 @@ pub fn time_table_render(props: &TimeTableRenderProps) -> Html {
     html! {
         <>
             { if let Some(lessons) = (*selected_group).clone() {
-                html! { <GroupDetailModal {lessons} on_close={on_close} /> }
+                html! { <GroupDetailModal {lessons} on_close={on_close} on_hide={props.on_hide_group.clone()} /> }
             } else { html! {} } }
 
             <div
@@ -95,7 +116,7 @@ pub fn time_table_render(props: &TimeTableRenderProps) -> Html {
                         })}
                     </div>
                 </div>
-
+                { notes_row }
 
                 <div class="d-flex flex-grow-1 w-100">
                     <div style="width: 60px; position: relative;" class="d-flex flex-column flex-shrink-0">
@@ -136,6 +157,26 @@ pub fn time_table_render(props: &TimeTableRenderProps) -> Html {
     }
 }
 
+fn render_note(note: &DayNote) -> Html {
+    let (cls, icon) = match note.kind {
+        DayNoteKind::Holiday => ("alert-success", "bi-sun"),
+        DayNoteKind::Event => ("alert-info", "bi-calendar-event"),
+        DayNoteKind::Announcement => ("alert-warning", "bi-megaphone"),
+    };
+    let time = note.time_range.as_ref()
+        .filter(|t| t.start.date() == t.end.date() && t.start.time() != NaiveTime::MIN)
+        .map(|t| format!("{} - {} ", t.start.format("%H:%M"), t.end.format("%H:%M")))
+        .unwrap_or_default();
+
+    html! {
+        <div class={classes!("alert", cls, "py-1", "px-2", "mb-1", "small", "text-truncate")} title={note.text.clone()}>
+            <i class={classes!("bi", icon, "me-1")}></i>
+            { time }
+            <strong>{ &note.title }</strong>
+        </div>
+    }
+}
+
 fn group_by_time(mut lessons: Vec<LessonBlock>) -> Vec<Vec<LessonBlock>> {
     lessons.sort_by_key(|l| l.time_range.start);
     let mut remaining = lessons;
@@ -0,0 +1,79 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum ExcuseStatus {
+    Excused,
+    Unexcused,
+    Pending,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Absence {
+    pub id: i64,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub reason: String,
+    pub text: String,
+    pub status: ExcuseStatus,
+    pub status_label: String, // the school's own label, e.g. "entsch."
+    pub excuse_text: String,
+    pub missed_subjects: Vec<String>, // one entry per missed lesson
+}
+
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct AbsenceTotals {
+    pub absences: usize,
+    pub lessons: usize,
+}
+
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct AbsenceSummary {
+    pub excused: AbsenceTotals,
+    pub unexcused: AbsenceTotals,
+    pub pending: AbsenceTotals,
+    pub per_subject: BTreeMap<String, [usize; 3]>, // lessons as [excused, unexcused, pending]
+}
+
+impl ExcuseStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExcuseStatus::Excused => "Excused",
+            ExcuseStatus::Unexcused => "Unexcused",
+            ExcuseStatus::Pending => "Pending",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            ExcuseStatus::Excused => 0,
+            ExcuseStatus::Unexcused => 1,
+            ExcuseStatus::Pending => 2,
+        }
+    }
+}
+
+impl AbsenceSummary {
+    pub fn from_absences<'a>(absences: impl IntoIterator<Item = &'a Absence>) -> Self {
+        let mut summary = Self::default();
+
+        for absence in absences {
+            let totals = match absence.status {
+                ExcuseStatus::Excused => &mut summary.excused,
+                ExcuseStatus::Unexcused => &mut summary.unexcused,
+                ExcuseStatus::Pending => &mut summary.pending,
+            };
+            totals.absences += 1;
+            totals.lessons += absence.missed_subjects.len();
+
+            for subject in &absence.missed_subjects {
+                summary.per_subject.entry(subject.clone()).or_default()[absence.status.index()] += 1;
+            }
+        }
+
+        summary
+    }
+}
@@ -0,0 +1,67 @@
+use crate::data_models::clean_models::untis::{Entity, TimeRange};
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+// one difference between the cached and the freshly fetched week of an entity
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TimetableChange {
+    pub entity: Entity,
+    pub subject: String,
+    pub time_range: TimeRange, // where the lesson is now, or was for removed lessons
+    pub kind: ChangeKind,
+    pub detected: NaiveDateTime,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Cancelled,
+    Moved { from: TimeRange },
+    TeacherChanged { from: Vec<String>, to: Vec<String> },
+    RoomChanged { from: Vec<String>, to: Vec<String> },
+}
+
+impl ChangeKind {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "bi-plus-circle",
+            ChangeKind::Removed => "bi-dash-circle",
+            ChangeKind::Cancelled => "bi-x-circle",
+            ChangeKind::Moved { .. } => "bi-arrow-left-right",
+            ChangeKind::TeacherChanged { .. } => "bi-person",
+            ChangeKind::RoomChanged { .. } => "bi-door-open",
+        }
+    }
+}
+
+impl TimetableChange {
+    pub fn describe(&self) -> String {
+        let when = format_slot(&self.time_range);
+        let list = |names: &[String]| if names.is_empty() { "-".to_string() } else { names.join(", ") };
+
+        match &self.kind {
+            ChangeKind::Added => format!("{} added on {}", self.subject, when),
+            ChangeKind::Removed => format!("{} on {} was removed", self.subject, when),
+            ChangeKind::Cancelled => format!("{} on {} is cancelled", self.subject, when),
+            ChangeKind::Moved { from } => format!("{} moved from {} to {}", self.subject, format_slot(from), when),
+            ChangeKind::TeacherChanged { from, to } => {
+                format!("{} on {}: teacher {} instead of {}", self.subject, when, list(to), list(from))
+            }
+            ChangeKind::RoomChanged { from, to } => {
+                format!("{} on {}: room {} instead of {}", self.subject, when, list(to), list(from))
+            }
+        }
+    }
+}
+
+fn format_slot(range: &TimeRange) -> String {
+    format!(
+        "{} {} {}",
+        range.start.weekday(),
+        range.start.format("%d.%m."),
+        range.start.format("%H:%M")
+    )
+}
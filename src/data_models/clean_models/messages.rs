@@ -0,0 +1,144 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum MessageFolder {
+    Inbox,
+    Sent,
+    ReadConfirmations,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MessagePerson {
+    pub id: Option<i64>,
+    pub display_name: String,
+    pub class_name: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Message {
+    pub id: i64,
+    pub folder: MessageFolder,
+    pub subject: String,
+    pub preview: String,
+    pub sent: NaiveDateTime,
+    pub sender: Option<MessagePerson>,
+    pub recipients: Vec<MessagePerson>,
+    pub is_read: bool,
+    pub has_attachments: bool,
+    pub is_reply: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MessageDetail {
+    pub id: i64,
+    pub subject: String,
+    pub content: String, // html stripped to plain text
+    pub sent: NaiveDateTime,
+    pub sender: Option<MessagePerson>,
+    pub recipients: Vec<MessagePerson>,
+    pub attachments: Vec<MessageAttachment>,
+    pub history: Vec<MessageDetail>,
+    pub confirmation: Option<ReadConfirmation>,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MessageAttachment {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ReadConfirmation {
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub can_confirm: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Mailbox {
+    pub inbox: Vec<Message>,
+    pub sent: Vec<Message>,
+    pub read_confirmations: Vec<Message>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MessageThread {
+    pub id: i64, // of the first message, the topic alone isn't unique
+    pub topic: String,
+    pub messages: Vec<Message>, // oldest first
+}
+
+impl MessageThread {
+    pub fn latest(&self) -> Option<&Message> {
+        self.messages.last()
+    }
+}
+
+impl Mailbox {
+    pub fn folder(&self, folder: MessageFolder) -> &[Message] {
+        match folder {
+            MessageFolder::Inbox => &self.inbox,
+            MessageFolder::Sent => &self.sent,
+            MessageFolder::ReadConfirmations => &self.read_confirmations,
+        }
+    }
+
+    // replies join the latest thread with the same topic and participants, anything else starts a new one
+    pub fn threads(&self, folder: MessageFolder) -> Vec<MessageThread> {
+        // the key of each thread, its topic and participants
+        let mut threads: Vec<(String, MessageThread)> = Vec::new();
+        let mut messages = self.folder(folder).to_vec();
+        messages.sort_by_key(|m| m.sent);
+
+        for message in messages {
+            let topic = thread_topic(&message.subject);
+            let key = format!("{}|{}", topic.to_lowercase(), participants(&message).join(","));
+            let is_reply = message.is_reply || topic != message.subject.trim();
+            match threads.iter_mut().rev().find(|(k, _)| *k == key).filter(|_| is_reply) {
+                Some((_, thread)) => thread.messages.push(message),
+                None => threads.push((key, MessageThread { id: message.id, topic, messages: vec![message] })),
+            }
+        }
+
+        let mut threads: Vec<MessageThread> = threads.into_iter().map(|(_, t)| t).collect();
+        threads.sort_by_key(|t| std::cmp::Reverse(t.latest().map(|m| m.sent)));
+        threads
+    }
+}
+
+// everyone a message is between, in a stable order
+fn participants(message: &Message) -> Vec<String> {
+    let mut participants: Vec<String> = message
+        .sender
+        .iter()
+        .chain(&message.recipients)
+        .map(|p| match p.id {
+            Some(id) => id.to_string(),
+            None => p.display_name.to_lowercase(),
+        })
+        .collect();
+    participants.sort();
+    participants.dedup();
+    participants
+}
+
+// replies and forwards share a thread with the message they answer
+pub fn thread_topic(subject: &str) -> String {
+    let mut topic = subject.trim();
+    loop {
+        let lower = topic.to_lowercase();
+        match ["re:", "aw:", "fwd:", "fw:", "wg:"].iter().find(|p| lower.starts_with(*p)) {
+            Some(prefix) => topic = topic[prefix.len()..].trim_start(),
+            None => break,
+        }
+    }
+    topic.to_string()
+}
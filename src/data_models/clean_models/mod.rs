@@ -1 +1,4 @@
-pub mod untis;
\ No newline at end of file
+pub mod untis;
+pub mod messages;
+pub mod absences;
+pub mod changes;
\ No newline at end of file
@@ -23,7 +23,7 @@ pub struct Department {
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Hash, Eq, Deserialize)]
 pub struct Teacher {
-    pub id: Option<i32>, // we only have the id if it's a class teacher
+    pub id: Option<i32>, // missing for teachers only known by name, e.g. without access to the master data
     pub short_name: String,
     pub long_name: String,
     pub display_name: String,
@@ -32,12 +32,22 @@ pub struct Teacher {
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Hash, Eq, Deserialize)]
 pub struct Room {
+    #[serde(default)]
+    pub id: Option<i32>,
     pub name: String,
+    #[serde(default)]
+    pub long_name: String,
+    #[serde(default)]
+    pub building: Option<String>,
+    #[serde(default)]
+    pub capacity: Option<u32>,
 }
 
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Hash, Eq, Deserialize)]
 pub struct Subject {
+    #[serde(default)]
+    pub id: Option<i32>,
     pub short_name: String,
     pub long_name: String,
     pub display_name: String,
@@ -49,10 +59,19 @@ pub struct Info {
     pub text: String,
 }
 
+// the logged-in student, whose personal timetable is fetched
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Hash, Eq, Deserialize)]
+pub struct Student {
+    pub id: i32,
+    pub name: String,
+}
+
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct WeekTimeTable {
     pub days: Vec<DayTimeTable>,
+    pub homework: Vec<Homework>, // only known for the user's own timetable
 }
 
 #[allow(dead_code)]
@@ -60,11 +79,33 @@ pub struct WeekTimeTable {
 pub struct DayTimeTable {
     pub date: NaiveDate,
     pub lessons: Vec<LessonBlock>,
+    pub notes: Vec<DayNote>, // holidays, events and announcements shown above the day
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DayNote {
+    pub kind: DayNoteKind,
+    pub title: String,
+    pub text: String,
+    pub time_range: Option<TimeRange>, // none for all-day notes
+    pub color_hex: String,
+    pub link: String,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DayNoteKind {
+    Holiday,
+    Event,
+    #[default]
+    Announcement,
 }
 
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct LessonBlock {
+    pub ids: Vec<i64>, // untis period ids, stable across refetches
     pub time_range: TimeRange,
     pub entities: Vec<Tracked<Entity>>,
     pub r#type: String,
@@ -73,6 +114,35 @@ pub struct LessonBlock {
     pub icons: Vec<String>,
     pub texts: Vec<HashMap<String, String>>, // may include "notesAll", "statusDetail", "name", "lessonText", "substitutionText" and "userName" as well as all texts in the text response
     pub link: String,
+    pub homework: Vec<Homework>, // given or due in this lesson
+    pub exam: Option<Exam>,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Homework {
+    pub id: i64,
+    pub subject: String,
+    pub teacher: String,
+    pub assigned: NaiveDate,
+    pub due: NaiveDate,
+    pub text: String,
+    pub remark: String,
+    pub completed: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Exam {
+    pub id: i64,
+    pub exam_type: String,
+    pub name: String,
+    pub subject: String,
+    pub teachers: Vec<String>,
+    pub rooms: Vec<String>,
+    pub classes: Vec<String>,
+    pub time_range: TimeRange,
+    pub text: String,
 }
 
 #[allow(dead_code)]
@@ -83,6 +153,35 @@ pub enum Entity {
     Room(Room),
     Subject(Subject),
     Info(Info),
+    Student(Student),
+}
+
+// the school's teachers, rooms and subjects by short name, lessons only name them
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct MasterData {
+    pub teachers: HashMap<String, Teacher>,
+    pub rooms: HashMap<String, Room>,
+    pub subjects: HashMap<String, Subject>,
+}
+
+// the kinds of timetables webuntis serves directly
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ResourceType {
+    Class,
+    Teacher,
+    Room,
+    Student,
+}
+
+// something a timetable can be requested for, with the entity it shows up as in lessons
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct Resource {
+    pub kind: ResourceType,
+    pub id: i32,
+    pub entity: Entity,
 }
 
 #[allow(dead_code)]
@@ -113,6 +212,75 @@ impl LessonBlock {
         self.time_range.start < other.time_range.end &&
             other.time_range.start < self.time_range.end
     }
+
+    pub fn is_exam(&self) -> bool {
+        self.r#type == "EXAM" || self.exam.is_some()
+    }
+
+    // the same course in every week, a subject and its planned teachers. a substitute doesn't change it,
+    // the planned teacher stays in the lesson as removed
+    pub fn group_key(&self) -> Option<String> {
+        let subject = self.entities.iter().find_map(|e| match &e.inner {
+            Entity::Subject(s) => Some(s.short_name.clone()),
+            _ => None,
+        })?;
+        let mut teachers: Vec<_> = self
+            .entities
+            .iter()
+            .filter(|e| matches!(e.status, ChangeStatus::Regular | ChangeStatus::Removed))
+            .filter_map(|e| match &e.inner {
+                Entity::Teacher(t) => Some(t.short_name.clone()),
+                _ => None,
+            })
+            .collect();
+        teachers.sort();
+        teachers.dedup();
+
+        Some(if teachers.is_empty() { subject } else { format!("{} ({})", subject, teachers.join(", ")) })
+    }
+
+    pub fn teaches(&self, subject: &str) -> bool {
+        self.entities.iter().any(|e| match &e.inner {
+            Entity::Subject(s) => s.short_name == subject || s.long_name == subject || s.display_name == subject,
+            _ => false,
+        })
+    }
+}
+
+impl Exam {
+    pub fn concerns(&self, name: &str) -> bool {
+        self.classes.iter().chain(&self.teachers).chain(&self.rooms).any(|n| n == name)
+    }
+}
+
+impl From<Exam> for LessonBlock {
+    fn from(exam: Exam) -> Self {
+        let regular = |inner| Tracked { inner, status: ChangeStatus::Regular };
+        let entities = std::iter::once(regular(Entity::Subject(Subject {
+            id: None,
+            short_name: exam.subject.clone(),
+            long_name: exam.subject.clone(),
+            display_name: exam.subject.clone(),
+        })))
+        .chain(exam.teachers.iter().map(|t| regular(Entity::Teacher(Teacher {
+            id: None,
+            short_name: t.clone(),
+            long_name: t.clone(),
+            display_name: t.clone(),
+        }))))
+        .chain(exam.rooms.iter().map(|r| regular(Entity::Room(Room { name: r.clone(), ..Default::default() }))))
+        .collect();
+
+        Self {
+            time_range: exam.time_range.clone(),
+            entities,
+            r#type: "EXAM".to_string(),
+            status: "REGULAR".to_string(),
+            color_hex: "fac444".to_string(),
+            exam: Some(exam),
+            ..Default::default()
+        }
+    }
 }
 
 impl Entity {
@@ -123,11 +291,153 @@ impl Entity {
             Entity::Room(r) => r.name.clone(),
             Entity::Subject(s) => s.short_name.clone(),
             Entity::Info(i) => i.text.clone(),
+            Entity::Student(s) => s.name.clone(),
+        }
+    }
+
+    // the long name where the master data has one, rooms with their building
+    pub fn long_name(&self) -> String {
+        let or_short = |long: &str, short: &str| if long.is_empty() { short.to_string() } else { long.to_string() };
+        match self {
+            Entity::Teacher(t) => or_short(&t.long_name, &t.short_name),
+            Entity::Subject(s) => or_short(&s.long_name, &s.short_name),
+            Entity::Room(r) => match &r.building {
+                Some(building) => format!("{} ({})", or_short(&r.long_name, &r.name), building),
+                None => or_short(&r.long_name, &r.name),
+            },
+            _ => self.name(),
+        }
+    }
+
+    // lowercase kind used in feed urls and calendar uids
+    pub fn category(&self) -> &'static str {
+        match self {
+            Entity::Class(_) => "class",
+            Entity::Teacher(_) => "teacher",
+            Entity::Room(_) => "room",
+            Entity::Subject(_) => "subject",
+            Entity::Info(_) => "info",
+            Entity::Student(_) => "student",
+        }
+    }
+}
+
+impl ResourceType {
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            ResourceType::Class => "CLASS",
+            ResourceType::Teacher => "TEACHER",
+            ResourceType::Room => "ROOM",
+            ResourceType::Student => "STUDENT",
         }
     }
 }
 
+impl MasterData {
+    // swaps an entity known only by name for its full master data entry
+    pub fn resolve(&self, entity: &mut Entity) {
+        match entity {
+            Entity::Teacher(t) => {
+                if let Some(full) = self.teachers.get(&t.short_name) {
+                    *t = full.clone();
+                }
+            }
+            Entity::Room(r) => {
+                if let Some(full) = self.rooms.get(&r.name) {
+                    *r = full.clone();
+                }
+            }
+            Entity::Subject(s) => {
+                if let Some(full) = self.subjects.get(&s.short_name) {
+                    *s = full.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl From<Class> for Resource {
+    fn from(class: Class) -> Self {
+        Self {
+            kind: ResourceType::Class,
+            id: class.id,
+            entity: Entity::Class(class),
+        }
+    }
+}
+
+impl Resource {
+    // unique per resource, used as a storage key
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.entity.category(), self.id)
+    }
+}
+
 impl WeekTimeTable {
+    pub fn resolve_entities(&mut self, master: &MasterData) {
+        for lesson in self.days.iter_mut().flat_map(|d| &mut d.lessons) {
+            for entity in &mut lesson.entities {
+                master.resolve(&mut entity.inner);
+            }
+        }
+    }
+
+    // drops the courses of groups the user isn't in
+    pub fn hide_groups(&mut self, hidden: &[String]) {
+        for day in &mut self.days {
+            day.lessons.retain(|l| l.group_key().is_none_or(|k| !hidden.contains(&k)));
+        }
+    }
+
+    // attaches each homework to the lessons of its subject on the day it was given and the day it is due
+    pub fn attach_homework(&mut self, homework: Vec<Homework>) {
+        for day in &mut self.days {
+            for lesson in &mut day.lessons {
+                lesson.homework = homework
+                    .iter()
+                    .filter(|h| h.assigned == day.date || h.due == day.date)
+                    .filter(|h| lesson.teaches(&h.subject))
+                    .cloned()
+                    .collect();
+            }
+        }
+        self.homework = homework;
+    }
+
+    // marks the lessons an exam is written in, exams outside of regular lessons get their own block
+    pub fn attach_exams(&mut self, exams: Vec<Exam>) {
+        for exam in exams {
+            let Some(day) = self.days.iter_mut().find(|d| d.date == exam.time_range.start.date()) else {
+                continue;
+            };
+
+            let exam_block = LessonBlock::from(exam.clone());
+            let mut matched = false;
+            for lesson in day.lessons.iter_mut().filter(|l| l.overlaps(&exam_block) && l.teaches(&exam.subject)) {
+                lesson.exam = Some(exam.clone());
+                matched = true;
+            }
+
+            if !matched {
+                day.lessons.push(exam_block);
+            }
+        }
+    }
+
+    pub fn homework_due(&self, from: NaiveDate, to: NaiveDate) -> Vec<Homework> {
+        let mut due: Vec<Homework> = self
+            .homework
+            .iter()
+            .chain(self.days.iter().flat_map(|d| d.lessons.iter().flat_map(|l| &l.homework)))
+            .filter(|h| h.due >= from && h.due <= to)
+            .cloned()
+            .collect();
+        due.sort_by_key(|h| (h.due, h.id));
+        due.dedup_by_key(|h| h.id);
+        due
+    }
+
     pub fn to_string_pretty(
         &self,
         render_classes: bool,
@@ -149,7 +459,13 @@ impl WeekTimeTable {
         }
 
         if time_points.is_empty() {
-            return "No lessons scheduled.".to_string();
+            let mut output = "No lessons scheduled.".to_string();
+            for day in &self.days {
+                for note in &day.notes {
+                    output.push_str(&format!("\n{}: {}", day.date.format("%d.%m."), note.title));
+                }
+            }
+            return output;
         }
 
         let sorted_points: Vec<(u32, u32)> = time_points.into_iter().collect();
@@ -171,6 +487,18 @@ impl WeekTimeTable {
             ));
         }
         output.push_str("|\n");
+
+        if self.days.iter().any(|d| !d.notes.is_empty()) {
+            output.push_str(&format!("| {:<width$} ", "", width = time_col_width));
+            for day in &self.days {
+                let mut notes = day.notes.iter().map(|n| n.title.clone()).collect::<Vec<_>>().join(" / ");
+                if notes.chars().count() > col_width {
+                    notes = notes.chars().take(col_width - 1).collect::<String>() + "…";
+                }
+                output.push_str(&format!("| {:^width$} ", notes, width = col_width));
+            }
+            output.push_str("|\n");
+        }
         output.push_str(&format!("{:-<width$}\n", "", width = total_width));
 
         for ((h1, m1), (h2, m2)) in slots {
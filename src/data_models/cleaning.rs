@@ -1,4 +1,10 @@
+use crate::data_models::clean_models::absences::*;
+use crate::data_models::clean_models::messages::*;
 use crate::data_models::clean_models::untis::*;
+use crate::data_models::response_models::untis_absences::*;
+use crate::data_models::response_models::untis_exams::*;
+use crate::data_models::response_models::untis_homework::*;
+use crate::data_models::response_models::untis_messages::*;
 use crate::data_models::response_models::untis_response_models::*;
 use crate::data_models::response_models::untis_timetables::*;
 use chrono::{NaiveDate, NaiveDateTime};
@@ -37,6 +43,66 @@ impl From<UntisTeacher> for Teacher {
     }
 }
 
+impl From<UntisTeacherEntry> for Teacher {
+    fn from(entry: UntisTeacherEntry) -> Self {
+        Teacher::from(entry.teacher)
+    }
+}
+
+impl From<UntisRoomEntry> for Room {
+    fn from(entry: UntisRoomEntry) -> Self {
+        Self {
+            id: Some(entry.room.id),
+            name: entry.room.short_name,
+            long_name: entry.room.long_name,
+            building: entry.building.map(|b| b.display_name).filter(|b| !b.is_empty()),
+            capacity: entry.capacity.or(entry.room.capacity),
+        }
+    }
+}
+
+impl From<UntisSubjectEntry> for Subject {
+    fn from(entry: UntisSubjectEntry) -> Self {
+        Self {
+            id: Some(entry.subject.id),
+            short_name: entry.subject.short_name,
+            long_name: entry.subject.long_name,
+            display_name: entry.subject.display_name,
+        }
+    }
+}
+
+impl From<&UntisResponse> for MasterData {
+    fn from(data: &UntisResponse) -> Self {
+        Self {
+            teachers: data
+                .teachers
+                .iter()
+                .flatten()
+                .cloned()
+                .map(Teacher::from)
+                .map(|t| (t.short_name.clone(), t))
+                .collect(),
+            rooms: data
+                .rooms
+                .iter()
+                .flatten()
+                .cloned()
+                .map(Room::from)
+                .map(|r| (r.name.clone(), r))
+                .collect(),
+            subjects: data
+                .subjects
+                .iter()
+                .flatten()
+                .cloned()
+                .map(Subject::from)
+                .map(|s| (s.short_name.clone(), s))
+                .collect(),
+        }
+    }
+}
+
 impl From<UntisDuration> for TimeRange {
     fn from(duration: UntisDuration) -> Self {
         let format = "%Y-%m-%dT%H:%M";
@@ -61,12 +127,15 @@ impl From<UntisResource> for Entity {
                 display_name: res.display_name,
             }),
             "subject" => Entity::Subject(Subject {
+                id: None,
                 short_name: res.short_name,
                 long_name: res.long_name,
                 display_name: res.display_name,
             }),
+            // only the name, like rooms named by exams, the rest comes from the master data
             "room" => Entity::Room(Room {
                 name: res.short_name,
+                ..Default::default()
             }),
             "klasse" | "class" => Entity::Class(Class {
                 id: 0,
@@ -143,6 +212,7 @@ impl From<UntisGridEntry> for LessonBlock {
         }
 
         Self {
+            ids: entry.ids,
             time_range: TimeRange::from(entry.duration),
             entities,
             r#type: entry.r#type,
@@ -151,9 +221,49 @@ impl From<UntisGridEntry> for LessonBlock {
             icons: entry.icons,
             texts: vec![texts_map],
             link: entry.link.unwrap_or_default(),
+            homework: vec![],
+            exam: None,
         }
     }
 }
+impl From<UntisDayNoteEntry> for DayNote {
+    fn from(entry: UntisDayNoteEntry) -> Self {
+        let kind = match entry.r#type.to_uppercase().as_str() {
+            "HOLIDAY" => DayNoteKind::Holiday,
+            "EVENT" | "SCHOOL_EVENT" | "CLASS_EVENT" => DayNoteKind::Event,
+            _ => DayNoteKind::Announcement,
+        };
+
+        let mut texts = [entry.notes_all, entry.lesson_text, entry.substitution_text, entry.status_detail]
+            .into_iter()
+            .flatten()
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>();
+        texts.dedup();
+
+        let title = entry
+            .name
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| if texts.is_empty() { entry.r#type.clone() } else { texts.remove(0) });
+
+        Self {
+            kind,
+            title,
+            text: texts.join("\n"),
+            // unlike grid entries these may span several days or lack a time, so parsing must not panic
+            time_range: entry.duration.and_then(|d| {
+                let format = "%Y-%m-%dT%H:%M";
+                Some(TimeRange {
+                    start: NaiveDateTime::parse_from_str(&d.start, format).ok()?,
+                    end: NaiveDateTime::parse_from_str(&d.end, format).ok()?,
+                })
+            }),
+            color_hex: entry.color.unwrap_or_default(),
+            link: entry.link.unwrap_or_default(),
+        }
+    }
+}
+
 impl From<UntisDayEntry> for DayTimeTable {
     fn from(entry: UntisDayEntry) -> Self {
         Self {
@@ -163,6 +273,206 @@ impl From<UntisDayEntry> for DayTimeTable {
                 .into_iter()
                 .map(LessonBlock::from)
                 .collect(),
+            notes: entry
+                .back_entries
+                .into_iter()
+                .chain(entry.day_entries)
+                .map(DayNote::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<UntisMessagePerson> for MessagePerson {
+    fn from(p: UntisMessagePerson) -> Self {
+        Self {
+            id: p.user_id,
+            display_name: p.display_name,
+            class_name: p.class_name,
+        }
+    }
+}
+
+impl From<(UntisMessagePreview, MessageFolder)> for Message {
+    fn from((preview, folder): (UntisMessagePreview, MessageFolder)) -> Self {
+        Self {
+            id: preview.id,
+            folder,
+            subject: preview.subject,
+            preview: html_to_text(&preview.content_preview),
+            sent: parse_message_time(&preview.sent_date_time),
+            sender: preview.sender.map(MessagePerson::from),
+            recipients: preview.recipients.into_iter().map(MessagePerson::from).collect(),
+            // sent messages don't carry a read flag
+            is_read: preview.is_message_read.unwrap_or(true),
+            has_attachments: preview.has_attachments,
+            is_reply: preview.is_reply,
+        }
+    }
+}
+
+impl From<UntisMessageAttachment> for MessageAttachment {
+    fn from(a: UntisMessageAttachment) -> Self {
+        Self {
+            id: match a.id {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            },
+            name: a.name,
+            url: a.src.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<UntisMessageDetail> for MessageDetail {
+    fn from(d: UntisMessageDetail) -> Self {
+        Self {
+            id: d.id,
+            subject: d.subject,
+            content: html_to_text(&d.content),
+            sent: parse_message_time(&d.sent_date_time),
+            sender: d.sender.map(MessagePerson::from),
+            recipients: d.recipients.into_iter().map(MessagePerson::from).collect(),
+            attachments: d
+                .attachments
+                .into_iter()
+                .chain(d.storage_attachments)
+                .map(MessageAttachment::from)
+                .collect(),
+            history: d.reply_history.into_iter().map(MessageDetail::from).collect(),
+            confirmation: d.request_confirmation.map(|c| ReadConfirmation {
+                confirmed_at: c.confirmation_date.as_deref().map(parse_message_time),
+                can_confirm: c.allow_send_request_confirmation,
+            }),
+        }
+    }
+}
+
+fn parse_message_time(s: &str) -> NaiveDateTime {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&chrono::Local).naive_local())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M"))
+        .unwrap_or_default()
+}
+
+// message bodies are html; we only show plain text so nothing from the server ends up in the dom
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut tag = None::<String>;
+
+    for c in html.chars() {
+        if let Some(t) = tag.as_mut() {
+            if c == '>' {
+                let name = t.trim_start_matches('/').split_whitespace().next().unwrap_or("").to_lowercase();
+                if matches!(name.trim_end_matches('/'), "br" | "p" | "div" | "li" | "tr") {
+                    text.push('\n');
+                }
+                tag = None;
+            } else {
+                t.push(c);
+            }
+        } else if c == '<' {
+            tag = Some(String::new());
+        } else {
+            text.push(c);
         }
     }
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    let mut lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    lines.join("\n").trim().to_string()
+}
+
+impl From<UntisAbsence> for Absence {
+    fn from(a: UntisAbsence) -> Self {
+        let status = match &a.excuse {
+            _ if a.is_excused => ExcuseStatus::Excused,
+            Some(e) if e.is_excused => ExcuseStatus::Excused,
+            // handed in but not decided on yet
+            Some(_) => ExcuseStatus::Pending,
+            None => ExcuseStatus::Unexcused,
+        };
+
+        Self {
+            id: a.id,
+            start: untis_date_time(a.start_date, a.start_time),
+            end: untis_date_time(a.end_date, a.end_time),
+            reason: a.reason.unwrap_or_default(),
+            text: a.text.unwrap_or_default(),
+            status,
+            status_label: a
+                .excuse
+                .as_ref()
+                .and_then(|e| e.excuse_status.clone())
+                .or(a.excuse_status)
+                .unwrap_or_default(),
+            excuse_text: a.excuse.and_then(|e| e.text).unwrap_or_default(),
+            missed_subjects: vec![],
+        }
+    }
+}
+
+impl From<UntisHomeworkData> for Vec<Homework> {
+    fn from(data: UntisHomeworkData) -> Self {
+        let subjects: HashMap<i64, String> = data.lessons.into_iter().map(|l| (l.id, l.subject)).collect();
+        let teachers: HashMap<i64, String> = data.teachers.into_iter().map(|t| (t.id, t.name)).collect();
+        let homework_teachers: HashMap<i64, i64> = data
+            .records
+            .into_iter()
+            .filter_map(|r| Some((r.homework_id, r.teacher_id?)))
+            .collect();
+
+        data.homeworks
+            .into_iter()
+            .map(|h| Homework {
+                id: h.id,
+                subject: subjects.get(&h.lesson_id).cloned().unwrap_or_default(),
+                teacher: homework_teachers
+                    .get(&h.id)
+                    .and_then(|t| teachers.get(t))
+                    .cloned()
+                    .unwrap_or_default(),
+                assigned: untis_date_time(h.date, 0).date(),
+                due: untis_date_time(h.due_date, 0).date(),
+                text: h.text,
+                remark: h.remark.unwrap_or_default(),
+                completed: h.completed,
+            })
+            .collect()
+    }
+}
+
+impl From<UntisExam> for Exam {
+    fn from(e: UntisExam) -> Self {
+        Self {
+            id: e.id,
+            exam_type: e.exam_type.unwrap_or_default(),
+            name: e.name.unwrap_or_default(),
+            subject: e.subject,
+            teachers: e.teachers,
+            rooms: e.rooms,
+            classes: e.student_class,
+            time_range: TimeRange {
+                start: untis_date_time(e.exam_date, e.start_time),
+                end: untis_date_time(e.exam_date, e.end_time),
+            },
+            text: e.text.unwrap_or_default(),
+        }
+    }
+}
+
+// the class register api encodes dates as yyyymmdd and times as hhmm integers
+fn untis_date_time(date: i32, time: i32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(date / 10000, (date / 100 % 100) as u32, (date % 100) as u32)
+        .and_then(|d| d.and_hms_opt((time / 100) as u32, (time % 100) as u32, 0))
+        .unwrap_or_default()
 }
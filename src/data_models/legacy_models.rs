@@ -0,0 +1,253 @@
+// the timetable models as they were stored in the single "cached_timetables" localStorage key.
+// postcard is positional, so the old cache can only be read with exactly these shapes
+use crate::data_models::clean_models::untis::{
+    Class, DayTimeTable, Department, Entity, Info, LessonBlock, Room, Subject, Teacher, TimeRange, Tracked,
+    WeekTimeTable,
+};
+use crate::persistence_manager::TimeTables;
+use crate::untis::untis_week::Week;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct LegacyTimeTableCache {
+    pub tables: HashMap<Week, (Option<NaiveDateTime>, LegacyTimeTables)>, // expiry and tables
+}
+
+pub type LegacyTimeTables = (HashMap<LegacyClass, LegacyWeekTimeTable>, Option<i32>);
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LegacyClass {
+    pub id: i32,
+    pub name: String,
+    pub class_teacher: Option<LegacyTeacher>,
+    pub department: Department,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LegacyTeacher {
+    pub id: Option<i32>,
+    pub short_name: String,
+    pub long_name: String,
+    pub display_name: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LegacyRoom {
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LegacySubject {
+    pub short_name: String,
+    pub long_name: String,
+    pub display_name: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct LegacyWeekTimeTable {
+    pub days: Vec<LegacyDayTimeTable>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LegacyDayTimeTable {
+    pub date: NaiveDate,
+    pub lessons: Vec<LegacyLessonBlock>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LegacyLessonBlock {
+    pub time_range: TimeRange,
+    pub entities: Vec<Tracked<LegacyEntity>>,
+    pub r#type: String,
+    pub status: String,
+    pub color_hex: String,
+    pub icons: Vec<String>,
+    pub texts: Vec<HashMap<String, String>>,
+    pub link: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LegacyEntity {
+    Teacher(LegacyTeacher),
+    Class(LegacyClass),
+    Room(LegacyRoom),
+    Subject(LegacySubject),
+    Info(Info),
+}
+
+// base64 of the zstd compressed postcard bytes
+pub fn decode_legacy_timetables(encoded: &str) -> Result<LegacyTimeTableCache, String> {
+    let compressed = STANDARD.decode(encoded.trim()).map_err(|e| format!("Base64 failed: {}", e))?;
+    let mut decompressed = Vec::new();
+    zstd::Decoder::new(&compressed[..])
+        .map_err(|e| format!("Decompression failed: {}", e))?
+        .read_to_end(&mut decompressed)
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    postcard::from_bytes(&decompressed).map_err(|e| format!("Postcard failed: {}", e))
+}
+
+impl From<LegacyTeacher> for Teacher {
+    fn from(t: LegacyTeacher) -> Self {
+        Self {
+            id: t.id,
+            short_name: t.short_name,
+            long_name: t.long_name,
+            display_name: t.display_name,
+        }
+    }
+}
+
+impl From<LegacyClass> for Class {
+    fn from(c: LegacyClass) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            class_teacher: c.class_teacher.map(Teacher::from),
+            department: c.department,
+        }
+    }
+}
+
+impl From<LegacyEntity> for Entity {
+    fn from(e: LegacyEntity) -> Self {
+        match e {
+            LegacyEntity::Teacher(t) => Entity::Teacher(t.into()),
+            LegacyEntity::Class(c) => Entity::Class(c.into()),
+            LegacyEntity::Room(r) => Entity::Room(Room { name: r.name, ..Default::default() }),
+            LegacyEntity::Subject(s) => Entity::Subject(Subject {
+                id: None,
+                short_name: s.short_name,
+                long_name: s.long_name,
+                display_name: s.display_name,
+            }),
+            LegacyEntity::Info(i) => Entity::Info(i),
+        }
+    }
+}
+
+// lesson ids, homework, exams and day notes weren't stored back then, they come with the next refetch
+impl From<LegacyLessonBlock> for LessonBlock {
+    fn from(l: LegacyLessonBlock) -> Self {
+        Self {
+            time_range: l.time_range,
+            entities: l
+                .entities
+                .into_iter()
+                .map(|e| Tracked { inner: e.inner.into(), status: e.status })
+                .collect(),
+            r#type: l.r#type,
+            status: l.status,
+            color_hex: l.color_hex,
+            icons: l.icons,
+            texts: l.texts,
+            link: l.link,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<LegacyWeekTimeTable> for WeekTimeTable {
+    fn from(t: LegacyWeekTimeTable) -> Self {
+        Self {
+            days: t
+                .days
+                .into_iter()
+                .map(|d| DayTimeTable {
+                    date: d.date,
+                    lessons: d.lessons.into_iter().map(LessonBlock::from).collect(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn into_time_tables((tables, own_class): LegacyTimeTables) -> TimeTables {
+    let tables = tables.into_iter().map(|(class, table)| (class.into(), table.into())).collect();
+    (tables, own_class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_models::clean_models::untis::ChangeStatus;
+
+    // written the way the old PersistenceManager::save_timetables did
+    fn encode(cache: &LegacyTimeTableCache) -> String {
+        let bytes = postcard::to_allocvec(cache).unwrap();
+        STANDARD.encode(zstd::encode_all(&bytes[..], 3).unwrap())
+    }
+
+    #[test]
+    fn migrates_an_old_cache() {
+        let class = LegacyClass {
+            id: 42,
+            name: "3AHIT".to_string(),
+            class_teacher: Some(LegacyTeacher {
+                id: Some(131),
+                short_name: "BER".to_string(),
+                long_name: "Berger".to_string(),
+                display_name: "Berger Thomas".to_string(),
+            }),
+            department: Department::default(),
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let lesson = LegacyLessonBlock {
+            time_range: TimeRange {
+                start: date.and_hms_opt(7, 50, 0).unwrap(),
+                end: date.and_hms_opt(8, 40, 0).unwrap(),
+            },
+            entities: vec![
+                Tracked {
+                    inner: LegacyEntity::Subject(LegacySubject {
+                        short_name: "AM".to_string(),
+                        long_name: "Mathematik".to_string(),
+                        display_name: "AM".to_string(),
+                    }),
+                    status: ChangeStatus::Regular,
+                },
+                Tracked {
+                    inner: LegacyEntity::Room(LegacyRoom { name: "E04".to_string() }),
+                    status: ChangeStatus::Changed,
+                },
+            ],
+            r#type: "NORMAL_TEACHING_PERIOD".to_string(),
+            status: "CHANGED".to_string(),
+            color_hex: "#f49f25".to_string(),
+            icons: vec![],
+            texts: vec![],
+            link: String::new(),
+        };
+        let week = Week { start: "2026-10-12".to_string(), end: "2026-10-18".to_string() };
+        let mut tables = HashMap::new();
+        tables.insert(class, LegacyWeekTimeTable { days: vec![LegacyDayTimeTable { date, lessons: vec![lesson] }] });
+        let mut cache = LegacyTimeTableCache::default();
+        cache.tables.insert(week.clone(), (None, (tables, Some(42))));
+
+        let decoded = decode_legacy_timetables(&encode(&cache)).unwrap();
+        let (_, (_, legacy)) = decoded.tables.into_iter().find(|(w, _)| *w == week).unwrap();
+        let (tables, own_class) = into_time_tables(legacy);
+
+        assert_eq!(own_class, Some(42));
+        let (class, table) = tables.into_iter().next().unwrap();
+        assert_eq!(class.name, "3AHIT");
+        assert_eq!(class.class_teacher.map(|t| t.short_name), Some("BER".to_string()));
+        let lesson = &table.days[0].lessons[0];
+        assert_eq!(lesson.time_range.start, date.and_hms_opt(7, 50, 0).unwrap());
+        assert_eq!(lesson.status, "CHANGED");
+        assert_eq!(lesson.entities[1].inner, Entity::Room(Room { name: "E04".to_string(), ..Default::default() }));
+        assert_eq!(lesson.entities[1].status, ChangeStatus::Changed);
+    }
+
+    #[test]
+    fn rejects_a_damaged_cache() {
+        assert!(decode_legacy_timetables("not base64!").is_err());
+        assert!(decode_legacy_timetables(&STANDARD.encode(b"not zstd")).is_err());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod clean_models;
 pub mod response_models;
-pub mod cleaning;
\ No newline at end of file
+pub mod cleaning;
+pub mod legacy_models;
\ No newline at end of file
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LettoLoginResponse {
+    pub token: Option<String>,
+    pub refresh_token: Option<String>,
+    pub error: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LettoTest {
+    pub id: i64,
+    pub name: String,
+    pub course_name: String,
+    pub r#type: String, // "TEST", "HOMEWORK", "EXERCISE", ...
+    pub open: bool,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub points: Option<f64>,
+    pub max_points: Option<f64>,
+    pub grade: Option<String>,
+}
+
+impl LettoTest {
+    pub fn due(&self) -> Option<chrono::NaiveDateTime> {
+        self.end_date.as_deref().and_then(|d| {
+            chrono::DateTime::parse_from_rfc3339(d)
+                .map(|dt| dt.with_timezone(&chrono::Local).naive_local())
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%dT%H:%M:%S"))
+                .ok()
+        })
+    }
+
+    pub fn has_result(&self) -> bool {
+        self.points.is_some() || self.grade.as_ref().is_some_and(|g| !g.is_empty())
+    }
+}
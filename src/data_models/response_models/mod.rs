@@ -1,3 +1,8 @@
 pub mod untis_response_models;
 pub mod untis_timetables;
-pub mod book2eat;
\ No newline at end of file
+pub mod untis_messages;
+pub mod untis_absences;
+pub mod untis_homework;
+pub mod untis_exams;
+pub mod book2eat;
+pub mod letto;
\ No newline at end of file
@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAbsencesResponse {
+    pub data: UntisAbsencesData,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAbsencesData {
+    #[serde(default)]
+    pub absences: Vec<UntisAbsence>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAbsence {
+    pub id: i64,
+    pub start_date: i32, // yyyymmdd
+    pub end_date: i32,
+    pub start_time: i32, // hhmm
+    pub end_time: i32,
+    pub reason: Option<String>,
+    pub text: Option<String>,
+    pub student_name: Option<String>,
+    pub excuse_status: Option<String>,
+    #[serde(default)]
+    pub is_excused: bool,
+    pub excuse: Option<UntisExcuse>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisExcuse {
+    pub id: i64,
+    pub text: Option<String>,
+    pub excuse_date: Option<i32>,
+    pub excuse_status: Option<String>,
+    #[serde(default)]
+    pub is_excused: bool,
+}
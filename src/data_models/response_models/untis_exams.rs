@@ -0,0 +1,38 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisExamsResponse {
+    pub data: UntisExamsData,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisExamsData {
+    #[serde(default)]
+    pub exams: Vec<UntisExam>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisExam {
+    pub id: i64,
+    pub exam_type: Option<String>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub student_class: Vec<String>,
+    pub exam_date: i32, // yyyymmdd
+    pub start_time: i32, // hhmm
+    pub end_time: i32,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub teachers: Vec<String>,
+    #[serde(default)]
+    pub rooms: Vec<String>,
+    pub text: Option<String>,
+    pub grade: Option<String>,
+}
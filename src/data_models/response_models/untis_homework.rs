@@ -0,0 +1,64 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomeworkResponse {
+    pub data: UntisHomeworkData,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomeworkData {
+    #[serde(default)]
+    pub records: Vec<UntisHomeworkRecord>,
+    #[serde(default)]
+    pub homeworks: Vec<UntisHomework>,
+    #[serde(default)]
+    pub teachers: Vec<UntisHomeworkTeacher>,
+    #[serde(default)]
+    pub lessons: Vec<UntisHomeworkLesson>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomeworkRecord {
+    pub homework_id: i64,
+    pub teacher_id: Option<i64>,
+    #[serde(default)]
+    pub element_ids: Vec<i64>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomework {
+    pub id: i64,
+    pub lesson_id: i64,
+    pub date: i32, // yyyymmdd
+    pub due_date: i32,
+    #[serde(default)]
+    pub text: String,
+    pub remark: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomeworkTeacher {
+    pub id: i64,
+    pub name: String,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisHomeworkLesson {
+    pub id: i64,
+    pub subject: String,
+    pub lesson_type: Option<String>,
+}
@@ -0,0 +1,93 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisInboxResponse {
+    #[serde(default)]
+    pub incoming_messages: Vec<UntisMessagePreview>,
+    #[serde(default)]
+    pub read_confirmation_messages: Vec<UntisMessagePreview>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisSentResponse {
+    #[serde(default)]
+    pub sent_messages: Vec<UntisMessagePreview>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisMessagePreview {
+    pub id: i64,
+    pub subject: String,
+    #[serde(default)]
+    pub content_preview: String,
+    pub sent_date_time: String,
+    pub sender: Option<UntisMessagePerson>,
+    #[serde(default)]
+    pub recipients: Vec<UntisMessagePerson>,
+    pub is_message_read: Option<bool>,
+    #[serde(default)]
+    pub has_attachments: bool,
+    #[serde(default)]
+    pub is_reply: bool,
+    #[serde(default)]
+    pub is_reply_allowed: bool,
+    #[serde(default)]
+    pub allow_message_deletion: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisMessagePerson {
+    pub user_id: Option<i64>,
+    pub display_name: String,
+    pub class_name: Option<String>,
+    pub image_url: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisMessageDetail {
+    pub id: i64,
+    pub subject: String,
+    #[serde(default)]
+    pub content: String,
+    pub sent_date_time: String,
+    pub sender: Option<UntisMessagePerson>,
+    #[serde(default)]
+    pub recipients: Vec<UntisMessagePerson>,
+    #[serde(default)]
+    pub attachments: Vec<UntisMessageAttachment>,
+    #[serde(default)]
+    pub storage_attachments: Vec<UntisMessageAttachment>,
+    #[serde(default)]
+    pub reply_history: Vec<UntisMessageDetail>,
+    pub request_confirmation: Option<UntisRequestConfirmation>,
+    #[serde(default)]
+    pub is_reply_allowed: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisMessageAttachment {
+    pub id: serde_json::Value,
+    pub name: String,
+    pub src: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisRequestConfirmation {
+    pub confirmation_date: Option<String>,
+    #[serde(default)]
+    pub allow_send_request_confirmation: bool,
+}
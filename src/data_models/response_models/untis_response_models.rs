@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
 use crate::data_models::response_models::untis_timetables::UntisDayEntry;
 
 #[allow(dead_code)]
@@ -16,15 +17,37 @@ pub struct UntisResponse {
     pub assignment_groups: Option<Vec<serde_json::Value>>,
     pub classes: Option<Vec<UntisClassEntry>>,
     pub resources: Option<Vec<serde_json::Value>>,
-    pub rooms: Option<Vec<serde_json::Value>>,
-    pub subjects: Option<Vec<serde_json::Value>>,
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub rooms: Option<Vec<UntisRoomEntry>>,
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub subjects: Option<Vec<UntisSubjectEntry>>,
     pub students: Option<Vec<serde_json::Value>>,
-    pub teachers: Option<Vec<serde_json::Value>>,
+    #[serde(default, deserialize_with = "lenient_list")]
+    pub teachers: Option<Vec<UntisTeacherEntry>>,
     pub error_code: Option<Vec<serde_json::Value>>,
     pub error_message: Option<Vec<serde_json::Value>>,
     pub request_id: Option<Vec<serde_json::Value>>,
 }
 
+// master data is only nice to have, an entry of an unexpected shape is skipped instead of failing the class list
+fn lenient_list<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let values: Option<Vec<serde_json::Value>> = Option::deserialize(deserializer)?;
+    Ok(values.map(|values| {
+        values
+            .into_iter()
+            .filter_map(|v| {
+                serde_json::from_value(v)
+                    .map_err(|e| log::warn!("Skipping an unexpected master data entry: {}", e))
+                    .ok()
+            })
+            .collect()
+    }))
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -66,6 +89,69 @@ pub struct UntisTeacher {
     pub display_name: String,
 }
 
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisTeacherEntry {
+    pub teacher: UntisTeacher,
+    pub department: Option<UntisDepartment>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisRoomEntry {
+    pub room: UntisRoomInfo,
+    pub building: Option<UntisBuilding>,
+    pub capacity: Option<u32>,
+    pub department: Option<UntisDepartment>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisRoomInfo {
+    pub id: i32,
+    pub short_name: String,
+    #[serde(default)]
+    pub long_name: String,
+    #[serde(default)]
+    pub display_name: String,
+    pub capacity: Option<u32>, // some schools have it on the room itself
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisBuilding {
+    pub id: i32,
+    #[serde(default)]
+    pub short_name: String,
+    #[serde(default)]
+    pub long_name: String,
+    #[serde(default)]
+    pub display_name: String,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisSubjectEntry {
+    pub subject: UntisSubjectInfo,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisSubjectInfo {
+    pub id: i32,
+    pub short_name: String,
+    #[serde(default)]
+    pub long_name: String,
+    #[serde(default)]
+    pub display_name: String,
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -75,3 +161,54 @@ pub struct UntisDepartment {
     pub long_name: String,
     pub display_name: String,
 }
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAppData {
+    pub user: UntisAppUser,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAppUser {
+    pub id: Option<i64>,
+    pub name: Option<String>,
+    pub person: Option<UntisAppPerson>,
+    #[serde(default)]
+    pub students: Vec<UntisAppPerson>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisAppPerson {
+    pub id: i64,
+    pub display_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_master_data_of_an_unexpected_shape() {
+        let body = r#"{
+            "classes": [],
+            "teachers": [
+                { "teacher": { "id": 131, "shortName": "BER", "longName": "Berger Thomas", "displayName": "Berger Thomas" } },
+                { "teacher": { "id": "n/a" } },
+                "unknown"
+            ],
+            "rooms": [{ "room": { "id": 12, "shortName": "E04" } }]
+        }"#;
+        let response: UntisResponse = serde_json::from_str(body).unwrap();
+
+        let teachers = response.teachers.unwrap();
+        assert_eq!(teachers.len(), 1);
+        assert_eq!(teachers[0].teacher.short_name, "BER");
+        assert_eq!(response.rooms.unwrap()[0].room.short_name, "E04");
+        assert!(response.subjects.is_none());
+    }
+}
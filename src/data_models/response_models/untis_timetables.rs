@@ -9,9 +9,29 @@ pub struct UntisDayEntry {
     pub resource_type: String,
     pub resource: UntisClassInfo,
     pub status: String,
-    pub day_entries: Vec<serde_json::Value>,
+    pub day_entries: Vec<UntisDayNoteEntry>,
     pub grid_entries: Vec<UntisGridEntry>,
-    pub back_entries: Vec<serde_json::Value>,
+    pub back_entries: Vec<UntisDayNoteEntry>,
+}
+
+// day entries (events, all-day notes) and back entries (holidays) share this shape
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UntisDayNoteEntry {
+    #[serde(default)]
+    pub ids: Vec<i64>,
+    pub duration: Option<UntisDuration>,
+    #[serde(default)]
+    pub r#type: String,
+    pub status: Option<String>,
+    pub status_detail: Option<String>,
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub notes_all: Option<String>,
+    pub lesson_text: Option<String>,
+    pub substitution_text: Option<String>,
+    pub link: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -2,6 +2,7 @@
 pub enum ApiError {
     Miscellaneous(String),
     Authentication(String),
+    Permission(String), // logged in, but not allowed to see this
     Parsing(String),
     Network(String),
 }
@@ -10,6 +11,7 @@ impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ApiError::Authentication(m) => write!(f, "Auth Error: {}", m),
+            ApiError::Permission(m) => write!(f, "Permission Error: {}", m),
             ApiError::Parsing(m) => write!(f, "Parsing Error: {}", m),
             ApiError::Network(m) => write!(f, "Network Error: {}", m),
             ApiError::Miscellaneous(m) => write!(f, "{}", m),
@@ -0,0 +1,139 @@
+use crate::data_models::response_models::letto::{LettoLoginResponse, LettoTest};
+use crate::errors::ApiError;
+use crate::persistence_manager::PersistenceManager;
+use crate::request_proxy::{request_proxy, ProxyResponse};
+use serde_json::json;
+use std::collections::HashMap;
+
+// the school identifier is the host of the school's letto instance, e.g. "letto.myschool.at"
+fn base_url(school_identifier: &str) -> String {
+    let host = school_identifier
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    format!("https://{}", host)
+}
+
+pub async fn get_letto_token() -> Result<(String, String), ApiError> {
+    let s = PersistenceManager::get_settings()?
+        .filter(|s| {
+            !s.letto_auth.school_identifier.is_empty()
+                && !s.letto_auth.user_identifier.is_empty()
+        })
+        .ok_or_else(|| ApiError::Authentication("Letto Credentials not set".into()))?;
+    let secret = s.letto_auth.secret().await?;
+    if secret.is_empty() {
+        return Err(ApiError::Authentication("Letto Credentials not set".into()));
+    }
+
+    let base = base_url(&s.letto_auth.school_identifier);
+    let body = json!({
+        "username": s.letto_auth.user_identifier,
+        "password": secret,
+    });
+
+    let headers = HashMap::from([(
+        "Content-Type".into(),
+        vec!["application/json; charset=utf-8".into()],
+    )]);
+
+    let res = request_proxy(
+        "POST",
+        &format!("{}/login/open/loginletto", base),
+        headers,
+        body.to_string(),
+    )
+    .await?;
+
+    Ok((base, parse_login(&res)?))
+}
+
+pub async fn get_tests(base: String, token: String) -> Result<Vec<LettoTest>, ApiError> {
+    let headers = HashMap::from([(
+        "Authorization".into(),
+        vec![format!("Bearer {}", token)],
+    )]);
+
+    let res = request_proxy(
+        "GET",
+        &format!("{}/letto/api/student/testlist", base),
+        headers,
+        "".to_string(),
+    )
+    .await?;
+
+    parse_tests(&res)
+}
+
+// letto has no public api documentation, so anything that doesn't look like the
+// expected response is reported instead of being read as "no tests"
+fn check_status(res: &ProxyResponse) -> Result<(), ApiError> {
+    match res.status {
+        0 | 200..=299 => Ok(()),
+        401 | 403 => Err(ApiError::Authentication(format!("Letto rejected the request with status {}", res.status))),
+        status => Err(ApiError::Network(format!("Letto answered with status {}", status))),
+    }
+}
+
+fn parse_login(res: &ProxyResponse) -> Result<String, ApiError> {
+    check_status(res)?;
+    let login: LettoLoginResponse =
+        serde_json::from_str(&res.body).map_err(|e| ApiError::Parsing(format!("Unexpected Letto login response: {}", e)))?;
+
+    if let Some(error) = login.error.filter(|e| !e.is_empty()) {
+        return Err(ApiError::Authentication(error));
+    }
+
+    login
+        .token
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| ApiError::Parsing("Unexpected Letto login response: no token".into()))
+}
+
+fn parse_tests(res: &ProxyResponse) -> Result<Vec<LettoTest>, ApiError> {
+    check_status(res)?;
+    serde_json::from_str(&res.body).map_err(|e| ApiError::Parsing(format!("Unexpected Letto test list: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> ProxyResponse {
+        ProxyResponse { status, headers: HashMap::new(), body: body.to_string() }
+    }
+
+    #[test]
+    fn reads_the_login_token() {
+        let res = response(200, r#"{"token":"abc","refreshToken":"def"}"#);
+        assert_eq!(parse_login(&res).unwrap(), "abc");
+    }
+
+    #[test]
+    fn rejects_an_unknown_login_response() {
+        assert!(matches!(parse_login(&response(200, "{}")), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_login(&response(200, "<html></html>")), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_login(&response(200, r#"{"error":"wrong password"}"#)), Err(ApiError::Authentication(_))));
+        assert!(matches!(parse_login(&response(401, "")), Err(ApiError::Authentication(_))));
+    }
+
+    #[test]
+    fn reads_the_test_list() {
+        let res = response(
+            200,
+            r#"[{"id":1,"name":"Test 1","courseName":"AM","type":"TEST","open":false,"endDate":"2025-03-01T10:00:00","points":7.5,"maxPoints":10}]"#,
+        );
+        let tests = parse_tests(&res).unwrap();
+        assert_eq!(tests.len(), 1);
+        assert!(tests[0].has_result());
+        assert!(tests[0].due().is_some());
+    }
+
+    #[test]
+    fn rejects_an_unknown_test_list() {
+        assert!(matches!(parse_tests(&response(200, r#"{"tests":[]}"#)), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_tests(&response(200, r#"[{"id":1,"title":"Test 1"}]"#)), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_tests(&response(500, "[]")), Err(ApiError::Network(_))));
+    }
+}
@@ -1,16 +1,29 @@
 
 mod components;
 mod request_proxy;
+mod notifications;
 mod persistence_manager;
+mod secret_store;
+mod qr_scanner;
+mod settings_transfer;
+mod timetable_store;
+mod storage_backend;
 mod data_models;
 mod untis;
 mod book2eat;
+mod letto;
 mod errors;
 
 use components::app::App;
+use persistence_manager::PersistenceManager;
+use secret_store::SecretStore;
 
 fn main() {
     console_error_panic_hook::set_once();
     wasm_logger::init(wasm_logger::Config::default());
-    yew::Renderer::<App>::new().render();
+    wasm_bindgen_futures::spawn_local(async {
+        PersistenceManager::init().await;
+        SecretStore::migrate().await;
+        yew::Renderer::<App>::new().render();
+    });
 }
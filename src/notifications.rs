@@ -0,0 +1,42 @@
+use crate::request_proxy::try_invoke;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct NotifyArgs<'a> {
+    options: NotificationOptions<'a>,
+}
+
+#[derive(Serialize)]
+struct NotificationOptions<'a> {
+    title: &'a str,
+    body: &'a str,
+}
+
+// shows a system notification through tauri-plugin-notification, asking for permission on first use
+pub async fn notify(title: &str, body: &str) -> Result<(), String> {
+    let granted = try_invoke("plugin:notification|is_permission_granted", wasm_bindgen::JsValue::NULL)
+        .await
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    if !granted {
+        let permission = try_invoke("plugin:notification|request_permission", wasm_bindgen::JsValue::NULL)
+            .await
+            .ok()
+            .and_then(|v| v.as_string());
+        if permission.as_deref() != Some("granted") {
+            return Err("Notifications are not permitted".to_string());
+        }
+    }
+
+    let args = serde_wasm_bindgen::to_value(&NotifyArgs {
+        options: NotificationOptions { title, body },
+    })
+    .map_err(|e| e.to_string())?;
+
+    try_invoke("plugin:notification|notify", args)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.as_string().unwrap_or("Failed to send notification".to_string()))
+}
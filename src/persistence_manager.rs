@@ -1,13 +1,21 @@
-use crate::data_models::clean_models::untis::{Class, WeekTimeTable};
+use crate::data_models::clean_models::changes::TimetableChange;
+use crate::data_models::clean_models::untis::{Class, Resource, ResourceType, WeekTimeTable};
+use crate::secret_store::SecretStore;
+use crate::storage_backend::{is_tauri, LocalStorageBackend, NativeFileBackend, StorageBackend};
 use crate::untis::untis_week::Week;
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::Read;
+use std::cell::RefCell;
+use std::rc::Rc;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlDocument, Storage};
+use web_sys::HtmlDocument;
+
+const LEGACY_TIMETABLES_KEY: &str = "cached_timetables";
+
+thread_local! {
+    static STORAGE: RefCell<Option<Rc<dyn StorageBackend>>> = const { RefCell::new(None) };
+}
 
 
 pub type TimeTables = (HashMap<Class, WeekTimeTable>, Option<i32>);
@@ -15,14 +23,71 @@ pub type TimeTables = (HashMap<Class, WeekTimeTable>, Option<i32>);
 #[allow(dead_code)]
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct TimeTableCache {
-    pub tables: HashMap<Week, (Option<NaiveDateTime>, TimeTables)>,
+    pub tables: HashMap<Week, CachedWeek>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CachedWeek {
+    pub fetched: NaiveDateTime,
+    pub expiry: Option<NaiveDateTime>, // none is never refetched
+    pub tables: TimeTables,
+}
+
+// a single timetable fetched directly instead of crawling all classes
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CachedTable {
+    pub resource: Resource,
+    pub fetched: NaiveDateTime,
+    pub expiry: Option<NaiveDateTime>,
+    pub table: WeekTimeTable,
+}
+
+// the resource lists and own class last fetched, so cached timetables still open without a connection
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KnownResources {
+    pub resources: HashMap<ResourceType, Vec<Resource>>,
+    pub own_class: Option<i32>,
 }
 
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Settings {
     pub untis_auth: AuthSettings,
     pub b2e_auth: AuthSettings,
+    #[serde(default)]
+    pub letto_auth: AuthSettings,
     pub visual_settings: VisualSettings,
+    #[serde(default)]
+    pub feed_settings: FeedSettings,
+    #[serde(default)]
+    pub refresh_settings: RefreshSettings,
+    #[serde(default)]
+    pub timetable_settings: TimetableSettings,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimetableSettings {
+    // fetches every class and derives teacher and room timetables from them,
+    // for accounts that aren't allowed to open those directly
+    pub crawl_all_classes: bool,
+    pub max_parallel_requests: u32, // untis requests in flight at once, more get throttled
+    pub hidden_groups: Vec<String>, // group keys of courses left out of the personal timetable
+    pub school_timezone: String,    // iana name, untis times are the school's wall clock
+}
+
+// webuntis is mostly used in austria and germany
+pub const DEFAULT_SCHOOL_TIMEZONE: &str = "Europe/Vienna";
+
+impl Default for TimetableSettings {
+    fn default() -> Self {
+        Self {
+            crawl_all_classes: false,
+            max_parallel_requests: 6,
+            hidden_groups: Vec::new(),
+            school_timezone: DEFAULT_SCHOOL_TIMEZONE.to_string(),
+        }
+    }
 }
 
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -30,12 +95,75 @@ pub struct VisualSettings {
     pub force_ascii_timetable: bool,
 }
 
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8765,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshSettings {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+    pub quiet_start_hour: u32, // no refreshes from this hour on ...
+    pub quiet_end_hour: u32,   // ... until this one, may wrap around midnight
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 30,
+            quiet_start_hour: 22,
+            quiet_end_hour: 6,
+        }
+    }
+}
+
+impl RefreshSettings {
+    pub fn is_quiet(&self, hour: u32) -> bool {
+        if self.quiet_start_hour <= self.quiet_end_hour {
+            (self.quiet_start_hour..self.quiet_end_hour).contains(&hour)
+        } else {
+            hour >= self.quiet_start_hour || hour < self.quiet_end_hour
+        }
+    }
+}
 
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct AuthSettings {
     pub school_identifier: String,
     pub user_identifier: String,
-    pub secret: String,
+    #[serde(default)]
+    pub secret_handle: String, // key in the SecretStore
+    #[serde(default)]
+    pub mode: UntisAuthMode, // only used for untis
+}
+
+// what the stored secret is, webuntis accepts either
+#[derive(Default, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum UntisAuthMode {
+    #[default]
+    Secret, // base32 totp secret from the mobile access qr code
+    Password,
+}
+
+impl AuthSettings {
+    // empty when no secret was saved yet
+    pub async fn secret(&self) -> Result<String, String> {
+        Ok(SecretStore::get(&self.secret_handle).await?.unwrap_or_default())
+    }
 }
 
 #[derive(Default, Clone, PartialEq, Debug)]
@@ -112,47 +240,55 @@ impl PersistenceManager {
         }
     }
 
-    pub fn save_timetables(tt: &TimeTableCache) -> Result<(), String> {
-        let bytes = postcard::to_allocvec(tt)
-            .map_err(|e| format!("Postcard failed: {}", e))?;
-
-        let compressed = zstd::encode_all(&bytes[..], 3)
-            .map_err(|e| format!("Compression failed: {}", e))?;
-
-        let encoded = STANDARD.encode(compressed);
-
-        Self::get_storage()?
-            .set_item("cached_timetables", &encoded)
-            .map_err(|_| "Failed to write to localStorage".to_string())?;
+    // the timetable cache used to live in a single localStorage key, the timetable store moves it to its records
+    pub fn get_legacy_timetables() -> Option<String> {
+        LocalStorageBackend::new().ok()?.get_item(LEGACY_TIMETABLES_KEY).ok().flatten()
+    }
 
-        Ok(())
+    pub fn remove_legacy_timetables() {
+        if let Ok(storage) = LocalStorageBackend::new()
+            && let Err(e) = storage.remove_item(LEGACY_TIMETABLES_KEY) {
+            log::warn!("Failed to remove the old timetable cache: {}", e);
+        }
     }
 
-    pub fn get_timetables() -> Result<Option<TimeTableCache>, String> {
-        let storage = Self::get_storage()?;
-        let value = storage.get_item("cached_timetables")
-            .map_err(|_| "Error reading from localStorage".to_string())?;
+    pub fn get_known_resources() -> Result<KnownResources, String> {
+        let value = Self::get_storage()?
+            .get_item("known_resources")
+            .map_err(|_| "Error reading from localStorage")?;
 
         match value {
-            Some(v) => {
-                let compressed_bytes = STANDARD.decode(v.trim())
-                    .map_err(|e| format!("Base64 decode failed: {}", e))?;
+            Some(v) => serde_json::from_str(&v).map_err(|e| format!("Failed to parse known resources: {}", e)),
+            None => Ok(KnownResources::default()),
+        }
+    }
 
-                let mut decompressed = Vec::new();
-                zstd::Decoder::new(&compressed_bytes[..])
-                    .map_err(|e| e.to_string())?
-                    .read_to_end(&mut decompressed)
-                    .map_err(|e| format!("Decompression failed: {}", e))?;
+    pub fn save_known_resources(known: &KnownResources) -> Result<(), String> {
+        let serialized = serde_json::to_string(known).map_err(|e| e.to_string())?;
+        Self::get_storage()?
+            .set_item("known_resources", &serialized)
+            .map_err(|_| "Failed to write to localStorage".to_string())
+    }
 
-                let decoded = postcard::from_bytes(&decompressed)
-                    .map_err(|e| format!("Postcard failed: {}", e))?;
+    // changes found on refreshes that the user hasn't dismissed yet
+    pub fn get_changes() -> Result<Vec<TimetableChange>, String> {
+        let value = Self::get_storage()?
+            .get_item("timetable_changes")
+            .map_err(|_| "Error reading from localStorage")?;
 
-                Ok(Some(decoded))
-            }
-            None => Ok(None),
+        match value {
+            Some(v) => serde_json::from_str(&v).map_err(|e| format!("Failed to parse changes: {}", e)),
+            None => Ok(vec![]),
         }
     }
 
+    pub fn save_changes(changes: &[TimetableChange]) -> Result<(), String> {
+        let serialized = serde_json::to_string(changes).map_err(|e| e.to_string())?;
+        Self::get_storage()?
+            .set_item("timetable_changes", &serialized)
+            .map_err(|_| "Failed to write to localStorage".to_string())
+    }
+
     pub fn clear_storage() -> Result<(), String> {
         Self::clear_cookies();
         Self::get_storage()?
@@ -160,13 +296,50 @@ impl PersistenceManager {
             .map_err(|_| "Failed to clear localStorage".to_string())
     }
 
-    fn get_storage() -> Result<Storage, String> {
-        let window = web_sys::window().ok_or("No global window found")?;
-        let storage = window
-            .local_storage()
-            .map_err(|_| "LocalStorage access denied (check permissions)")?
-            .ok_or("LocalStorage is not available in this environment")?;
-        Ok(storage)
+    // picks the storage backend, has to run before anything is read
+    pub async fn init() {
+        let backend: Rc<dyn StorageBackend> = if is_tauri() {
+            match NativeFileBackend::load().await {
+                Ok(native) => {
+                    Self::migrate_from_local_storage(&native);
+                    Rc::new(native)
+                }
+                Err(e) => {
+                    log::error!("Falling back to localStorage: {}", e);
+                    match LocalStorageBackend::new() {
+                        Ok(local) => Rc::new(local),
+                        Err(_) => return,
+                    }
+                }
+            }
+        } else {
+            match LocalStorageBackend::new() {
+                Ok(local) => Rc::new(local),
+                Err(_) => return,
+            }
+        };
+        STORAGE.with(|s| *s.borrow_mut() = Some(backend));
+    }
+
+    // earlier tauri builds kept everything in the webview's localStorage
+    fn migrate_from_local_storage(native: &NativeFileBackend) {
+        if native.get_item("user_settings").ok().flatten().is_some() {
+            return;
+        }
+        let Ok(local) = LocalStorageBackend::new() else {
+            return;
+        };
+        for key in ["user_settings", "JSESSIONID", "Tenant-Id", "schoolname", "timetable_changes"] {
+            if let Ok(Some(value)) = local.get_item(key) {
+                let _ = native.set_item(key, &value);
+            }
+        }
+    }
+
+    pub fn get_storage() -> Result<Rc<dyn StorageBackend>, String> {
+        STORAGE
+            .with(|s| s.borrow().clone())
+            .ok_or("Storage is not available in this environment".to_string())
     }
 }
 
@@ -0,0 +1,52 @@
+use crate::request_proxy::try_invoke;
+use crate::storage_backend::is_tauri;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+#[derive(Serialize)]
+struct ScanArgs {
+    windowed: bool,
+    formats: Vec<&'static str>,
+}
+
+#[derive(Deserialize)]
+struct Scanned {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct Permissions {
+    camera: String,
+}
+
+// the barcode scanner plugin only exists in the mobile builds
+pub fn can_scan() -> bool {
+    let user_agent = web_sys::window()
+        .and_then(|w| w.navigator().user_agent().ok())
+        .unwrap_or_default();
+    is_tauri() && ["Android", "iPhone", "iPad"].iter().any(|p| user_agent.contains(p))
+}
+
+// opens the camera fullscreen and resolves with the content of the first qr code found
+pub async fn scan_qr() -> Result<String, String> {
+    let permissions = try_invoke("plugin:barcode-scanner|request_permissions", JsValue::NULL)
+        .await
+        .map_err(js_error)?;
+    let permissions: Permissions = serde_wasm_bindgen::from_value(permissions).map_err(|e| e.to_string())?;
+    if permissions.camera != "granted" {
+        return Err("Camera access was denied".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&ScanArgs {
+        windowed: false,
+        formats: vec!["QR_CODE"],
+    })
+    .map_err(|e| e.to_string())?;
+    let scanned = try_invoke("plugin:barcode-scanner|scan", args).await.map_err(js_error)?;
+    let scanned: Scanned = serde_wasm_bindgen::from_value(scanned).map_err(|e| e.to_string())?;
+    Ok(scanned.content)
+}
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
@@ -4,6 +4,8 @@ use wasm_bindgen::prelude::*;
 
 #[derive(Deserialize, Debug)]
 pub struct ProxyResponse {
+    #[serde(default)]
+    pub status: u16,
     pub headers: HashMap<String, Vec<String>>,
     pub body: String,
 }
@@ -18,8 +20,9 @@ struct ProxyArgs<'a> {
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    // rejects with an error message, e.g. when the proxied request can't reach the server
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    pub async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
 }
 
 // CORS disallows the requests to other domains, so we need a proxy
@@ -37,6 +40,8 @@ pub async fn request_proxy(
     })
         .map_err(|e| e.to_string())?;
 
-    let response_js = invoke("proxy", args).await;
+    let response_js = try_invoke("proxy", args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
     serde_wasm_bindgen::from_value::<ProxyResponse>(response_js).map_err(|e| e.to_string())
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use crate::persistence_manager::PersistenceManager;
+use crate::request_proxy::try_invoke;
+use crate::storage_backend::is_tauri;
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+// handles referenced from the settings, the secrets themselves never end up in them
+pub const UNTIS_SECRET: &str = "untis-secret";
+pub const B2E_SECRET: &str = "b2e-secret";
+pub const LETTO_SECRET: &str = "letto-secret";
+pub const ALL_SECRETS: [&str; 3] = [UNTIS_SECRET, B2E_SECRET, LETTO_SECRET];
+
+#[derive(Serialize)]
+struct SecretArgs<'a> {
+    handle: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+}
+
+// the os keychain in the tauri build (or an encrypted file where there is none),
+// a plain browser has nothing better than the regular storage under a separate key
+pub struct SecretStore {}
+
+impl SecretStore {
+    pub async fn get(handle: &str) -> Result<Option<String>, String> {
+        if handle.is_empty() {
+            return Ok(None);
+        }
+        if is_tauri() {
+            Ok(invoke_secret("secret_get", handle, None).await?.as_string())
+        } else {
+            PersistenceManager::get_storage()?.get_item(&browser_key(handle))
+        }
+    }
+
+    pub async fn set(handle: &str, value: &str) -> Result<(), String> {
+        if is_tauri() {
+            invoke_secret("secret_set", handle, Some(value)).await.map(|_| ())
+        } else {
+            PersistenceManager::get_storage()?.set_item(&browser_key(handle), value)
+        }
+    }
+
+    pub async fn delete(handle: &str) -> Result<(), String> {
+        if is_tauri() {
+            invoke_secret("secret_delete", handle, None).await.map(|_| ())
+        } else {
+            PersistenceManager::get_storage()?.remove_item(&browser_key(handle))
+        }
+    }
+
+    // settings of older versions kept the secrets in plain text, they are moved into the store once.
+    // works on the raw json since the settings model no longer has a field for them
+    pub async fn migrate() {
+        let Ok(storage) = PersistenceManager::get_storage() else {
+            return;
+        };
+        let Some(mut settings) = storage
+            .get_item("user_settings")
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_str::<Value>(&v).ok())
+        else {
+            return;
+        };
+
+        let mut migrated = false;
+        for (key, handle) in [("untis_auth", UNTIS_SECRET), ("b2e_auth", B2E_SECRET), ("letto_auth", LETTO_SECRET)] {
+            let Some(auth) = settings.get_mut(key).and_then(|a| a.as_object_mut()) else {
+                continue;
+            };
+            let Some(secret) = auth.remove("secret") else {
+                continue;
+            };
+
+            if let Some(secret) = secret.as_str().filter(|s| !s.is_empty())
+                && let Err(e) = Self::set(handle, secret).await
+            {
+                // the plain text copy stays until the store works, so nothing is lost
+                log::error!("Failed to migrate the {} secret: {}", key, e);
+                return;
+            }
+            auth.insert("secret_handle".to_string(), Value::from(handle));
+            migrated = true;
+        }
+
+        if migrated && let Err(e) = storage.set_item("user_settings", &settings.to_string()) {
+            log::error!("Failed to save the migrated settings: {}", e);
+        }
+    }
+}
+
+fn browser_key(handle: &str) -> String {
+    format!("secret:{}", handle)
+}
+
+async fn invoke_secret(command: &str, handle: &str, value: Option<&str>) -> Result<JsValue, String> {
+    let args = serde_wasm_bindgen::to_value(&SecretArgs { handle, value }).map_err(|e| e.to_string())?;
+    try_invoke(command, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+}
@@ -0,0 +1,126 @@
+use crate::persistence_manager::{PersistenceManager, Settings};
+use crate::secret_store::{SecretStore, ALL_SECRETS};
+use crate::timetable_store::TimetableStore;
+use argon2::Argon2;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+// an export is PREFIX + base64 of [version, salt, nonce, ciphertext]. the ciphertext is the compressed bundle,
+// encrypted with a key derived from the passphrase
+const PREFIX: &str = "altis:";
+const VERSION: u8 = 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+// the bundle's own layout stays fixed. settings are json so exports of older builds still fill in
+// new fields with their defaults, the cache carries its own format version
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    settings: String,
+    secrets: Vec<(String, String)>, // handle and secret, the settings only reference them
+    cache: Option<Vec<u8>>,
+}
+
+// the export contains the untis secret and passwords, so it is always encrypted
+pub async fn export_settings(passphrase: &str, include_cache: bool) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err("Enter a passphrase to protect the export".to_string());
+    }
+
+    let mut secrets = vec![];
+    for handle in ALL_SECRETS {
+        if let Some(secret) = SecretStore::get(handle).await? {
+            secrets.push((handle.to_string(), secret));
+        }
+    }
+
+    let settings = PersistenceManager::get_settings()?.unwrap_or_default();
+    let bundle = SettingsBundle {
+        settings: serde_json::to_string(&settings).map_err(|e| format!("Serialization failed: {}", e))?,
+        secrets,
+        cache: if include_cache { Some(TimetableStore::export_weeks().await?) } else { None },
+    };
+
+    let serialized = postcard::to_allocvec(&bundle).map_err(|e| format!("Postcard failed: {}", e))?;
+    let compressed = zstd::encode_all(&serialized[..], 19).map_err(|e| format!("Compression failed: {}", e))?;
+
+    let mut bytes = vec![VERSION];
+    bytes.extend(encrypt(passphrase, &compressed)?);
+    Ok(format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(bytes)))
+}
+
+// replaces the settings and secrets, cached weeks in the export are added to the cache
+pub async fn import_settings(data: &str, passphrase: &str) -> Result<(), String> {
+    let encoded = data
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or("This is not an Altis settings export")?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|_| "The export is damaged or incomplete")?;
+
+    let compressed = match bytes.as_slice() {
+        [VERSION, ..] if passphrase.is_empty() => return Err("Enter the export's passphrase".to_string()),
+        [VERSION, payload @ ..] => decrypt(passphrase, payload)?,
+        [_, ..] => return Err("This export was created by a different version of Altis".to_string()),
+        [] => return Err("The export is empty".to_string()),
+    };
+
+    let mut serialized = Vec::new();
+    zstd::Decoder::new(&compressed[..])
+        .and_then(|mut d| d.read_to_end(&mut serialized))
+        .map_err(|_| "The export is damaged or incomplete")?;
+    let bundle: SettingsBundle =
+        postcard::from_bytes(&serialized).map_err(|_| "The export doesn't contain valid settings")?;
+    let settings: Settings =
+        serde_json::from_str(&bundle.settings).map_err(|e| format!("The export doesn't contain valid settings: {}", e))?;
+
+    for (handle, secret) in bundle.secrets.iter().filter(|(h, _)| ALL_SECRETS.contains(&h.as_str())) {
+        SecretStore::set(handle, secret).await?;
+    }
+    PersistenceManager::save_settings(&settings)?;
+
+    // a cache from a build with other data models is left out, it would only be refetched anyway
+    if let Some(cache) = bundle.cache
+        && !TimetableStore::import_weeks(&cache).await? {
+        log::info!("Skipping the exported timetable cache, it was written in another format");
+    }
+    Ok(())
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn encrypt(passphrase: &str, plain: &[u8]) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| e.to_string())?;
+    getrandom::getrandom(&mut nonce).map_err(|e| e.to_string())?;
+
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), plain)
+        .map_err(|_| "Encryption failed")?;
+    Ok([&salt[..], &nonce[..], &ciphertext[..]].concat())
+}
+
+fn decrypt(passphrase: &str, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < SALT_LEN + NONCE_LEN {
+        return Err("The export is damaged or incomplete".to_string());
+    }
+    let (salt, rest) = sealed.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    // authentication fails the same way for a wrong passphrase and tampered data, the former is far more likely
+    cipher(passphrase, salt)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase".to_string())
+}
@@ -0,0 +1,165 @@
+use crate::request_proxy::try_invoke;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Storage;
+
+// synchronous key-value storage behind PersistenceManager
+pub trait StorageBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String>;
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String>;
+    fn remove_item(&self, key: &str) -> Result<(), String>;
+    fn clear(&self) -> Result<(), String>;
+}
+
+// plain browser builds
+pub struct LocalStorageBackend {
+    storage: Storage,
+}
+
+impl LocalStorageBackend {
+    pub fn new() -> Result<Self, String> {
+        let window = web_sys::window().ok_or("No global window found")?;
+        let storage = window
+            .local_storage()
+            .map_err(|_| "LocalStorage access denied (check permissions)")?
+            .ok_or("LocalStorage is not available in this environment")?;
+        Ok(Self { storage })
+    }
+}
+
+impl StorageBackend for LocalStorageBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        self.storage.get_item(key).map_err(|_| "Error reading from localStorage".to_string())
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        self.storage.set_item(key, value).map_err(|_| "Failed to write to localStorage".to_string())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), String> {
+        self.storage.remove_item(key).map_err(|_| "Failed to write to localStorage".to_string())
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.storage.clear().map_err(|_| "Failed to clear localStorage".to_string())
+    }
+}
+
+// tauri builds, files in the app data directory. everything is loaded once on startup,
+// reads are served from memory and writes are sent to the backend in the background.
+// a failed write is retried with the next one, which then reports the failure to its caller
+pub struct NativeFileBackend {
+    mirror: RefCell<HashMap<String, String>>,
+    unsaved: Rc<RefCell<HashSet<String>>>,
+    last_error: Rc<RefCell<Option<String>>>,
+}
+
+#[derive(Serialize)]
+struct SetArgs<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct KeyArgs<'a> {
+    key: &'a str,
+}
+
+impl NativeFileBackend {
+    pub async fn load() -> Result<Self, String> {
+        let loaded = try_invoke("storage_load", JsValue::NULL)
+            .await
+            .map_err(|e| e.as_string().unwrap_or("Failed to load the app storage".to_string()))?;
+        let mirror = serde_wasm_bindgen::from_value(loaded).map_err(|e| e.to_string())?;
+        Ok(Self {
+            mirror: RefCell::new(mirror),
+            unsaved: Rc::default(),
+            last_error: Rc::default(),
+        })
+    }
+
+    // writes the mirrored state of a key, a removed key is removed from the file as well
+    fn write(&self, key: String) -> Result<(), String> {
+        let (command, args) = match self.mirror.borrow().get(&key) {
+            Some(value) => ("storage_set", serde_wasm_bindgen::to_value(&SetArgs { key: &key, value })),
+            None => ("storage_remove", serde_wasm_bindgen::to_value(&KeyArgs { key: &key })),
+        };
+        let args = args.map_err(|e| e.to_string())?;
+        let unsaved = self.unsaved.clone();
+        let last_error = self.last_error.clone();
+        spawn_local(async move {
+            match try_invoke(command, args).await {
+                Ok(_) => {
+                    unsaved.borrow_mut().remove(&key);
+                }
+                Err(e) => {
+                    let e = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+                    log::error!("{} failed for {}: {}", command, key, e);
+                    unsaved.borrow_mut().insert(key);
+                    *last_error.borrow_mut() = Some(e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    // writes the key and every earlier one that failed, errors if one of them did
+    fn sync(&self, key: &str) -> Result<(), String> {
+        let earlier = self.last_error.borrow_mut().take();
+        let mut keys: Vec<String> = self.unsaved.borrow().iter().cloned().collect();
+        if !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+        for key in keys {
+            self.write(key)?;
+        }
+        match earlier {
+            Some(e) => Err(format!("Failed to save to the app storage: {}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl StorageBackend for NativeFileBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.mirror.borrow().get(key).cloned())
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        self.mirror.borrow_mut().insert(key.to_string(), value.to_string());
+        self.sync(key)
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), String> {
+        self.mirror.borrow_mut().remove(key);
+        self.sync(key)
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.mirror.borrow_mut().clear();
+        self.unsaved.borrow_mut().clear();
+        let last_error = self.last_error.clone();
+        spawn_local(async move {
+            if let Err(e) = try_invoke("storage_clear", JsValue::NULL).await {
+                let e = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+                log::error!("storage_clear failed: {}", e);
+                *last_error.borrow_mut() = Some(e);
+            }
+        });
+        match self.last_error.borrow_mut().take() {
+            Some(e) => Err(format!("Failed to save to the app storage: {}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+// the tauri runtime injects its api into the webview, a plain browser doesn't have it
+pub fn is_tauri() -> bool {
+    web_sys::window()
+        .and_then(|w| js_sys::Reflect::get(&w, &JsValue::from_str("__TAURI__")).ok())
+        .is_some_and(|t| !t.is_undefined())
+}
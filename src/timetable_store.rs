@@ -0,0 +1,476 @@
+use crate::data_models::clean_models::untis::Resource;
+use crate::data_models::legacy_models::{decode_legacy_timetables, into_time_tables};
+use crate::persistence_manager::{CachedTable, CachedWeek, PersistenceManager, TimeTableCache};
+use crate::request_proxy::try_invoke;
+use crate::storage_backend::is_tauri;
+use crate::untis::untis_week::Week;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Local, NaiveDateTime};
+use js_sys::{Array, Promise, Uint8Array};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::Read;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "altis";
+const DB_VERSION: u32 = 1;
+const WEEKS: &str = "weeks"; // compressed CachedWeek per week start, CachedTable per week start and resource
+const META: &str = "meta"; // WeekMeta per record, read without touching the large records
+
+// first byte of every record. postcard is positional and ignores serde defaults, so this has to be
+// bumped whenever anything cached (WeekTimeTable, Entity, Resource, ...) changes shape
+const CACHE_FORMAT: u8 = 1;
+
+// the least recently used weeks are evicted above this
+const MAX_CACHE_BYTES: u32 = 20 * 1024 * 1024;
+
+thread_local! {
+    static RECORDS: RefCell<Option<Records>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WeekMeta {
+    week: Week,
+    #[serde(default)]
+    scope: Option<String>, // none for a whole week of the class crawl, the resource key for a single timetable
+    size: u32,
+    last_access: f64, // ms since epoch
+}
+
+impl WeekMeta {
+    fn key(&self) -> String {
+        record_key(&self.week, self.scope.as_deref())
+    }
+}
+
+// one record per week (or per week and resource), so saving one doesn't rewrite all others
+pub struct TimetableStore {}
+
+impl TimetableStore {
+    pub async fn get_week(week: &Week) -> Result<Option<CachedWeek>, String> {
+        let records = Self::records().await?;
+        Self::read_record(&records, week, None).await
+    }
+
+    pub async fn put_week(week: &Week, cached: &CachedWeek) -> Result<(), String> {
+        let records = Self::records().await?;
+        Self::write_record(&records, week, None, cached).await
+    }
+
+    pub async fn get_table(week: &Week, resource: &Resource) -> Result<Option<CachedTable>, String> {
+        let records = Self::records().await?;
+        Self::read_record(&records, week, Some(&resource.key())).await
+    }
+
+    pub async fn put_table(week: &Week, cached: &CachedTable) -> Result<(), String> {
+        let records = Self::records().await?;
+        Self::write_record(&records, week, Some(&cached.resource.key()), cached).await
+    }
+
+    pub async fn all_weeks() -> Result<TimeTableCache, String> {
+        let records = Self::records().await?;
+        let mut cache = TimeTableCache::default();
+        for meta in Self::all_meta(&records).await?.into_iter().filter(|m| m.scope.is_none()) {
+            if let Some(cached) = Self::read_all_entry(&records, &meta).await? {
+                cache.tables.insert(meta.week, cached);
+            }
+        }
+        Ok(cache)
+    }
+
+    // all whole weeks in the record format, for the settings export
+    pub async fn export_weeks() -> Result<Vec<u8>, String> {
+        encode(&Self::all_weeks().await?)
+    }
+
+    // false when the export was written in another cache format and nothing was imported
+    pub async fn import_weeks(bytes: &[u8]) -> Result<bool, String> {
+        let Some(cache) = decode::<TimeTableCache>(bytes)? else {
+            return Ok(false);
+        };
+        for (week, cached) in cache.tables {
+            Self::put_week(&week, &cached).await?;
+        }
+        Ok(true)
+    }
+
+    // every single timetable fetched directly, with the week it belongs to
+    pub async fn all_tables() -> Result<Vec<(Week, CachedTable)>, String> {
+        let records = Self::records().await?;
+        let mut tables = Vec::new();
+        for meta in Self::all_meta(&records).await?.into_iter().filter(|m| m.scope.is_some()) {
+            if let Some(cached) = Self::read_all_entry(&records, &meta).await? {
+                tables.push((meta.week, cached));
+            }
+        }
+        Ok(tables)
+    }
+
+    // marks every record as expired. that isn't an access, so the eviction order stays as it is
+    pub async fn expire_all(expiry: NaiveDateTime) -> Result<(), String> {
+        let records = Self::records().await?;
+        for meta in Self::all_meta(&records).await? {
+            if meta.scope.is_none() {
+                if let Some(mut cached) = Self::read_all_entry::<CachedWeek>(&records, &meta).await? {
+                    cached.expiry = Some(expiry);
+                    Self::rewrite_record(&records, meta, &cached).await?;
+                }
+            } else if let Some(mut cached) = Self::read_all_entry::<CachedTable>(&records, &meta).await? {
+                cached.expiry = Some(expiry);
+                Self::rewrite_record(&records, meta, &cached).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // number of records and their compressed size in bytes
+    pub async fn usage() -> Result<(usize, u32), String> {
+        let records = Self::records().await?;
+        let meta = Self::all_meta(&records).await?;
+        Ok((meta.len(), meta.iter().map(|m| m.size).sum()))
+    }
+
+    pub async fn clear() -> Result<(), String> {
+        let records = Self::records().await?;
+        for store in [WEEKS, META] {
+            records.clear(store).await?;
+        }
+        Ok(())
+    }
+
+    async fn read_record<T: DeserializeOwned>(records: &Records, week: &Week, scope: Option<&str>) -> Result<Option<T>, String> {
+        let key = record_key(week, scope);
+        let Some(bytes) = records.get(WEEKS, &key).await? else {
+            return Ok(None);
+        };
+
+        let cached = match decode(&bytes) {
+            Ok(Some(cached)) => cached,
+            // written by an older version of the data models, refetched like a missing record
+            Ok(None) => {
+                Self::delete_record(records, &key).await?;
+                return Ok(None);
+            }
+            // the record is overwritten once it has been refetched
+            Err(e) => return Err(format!("Cached record {} is damaged: {}", key, e)),
+        };
+
+        Self::touch(records, week, scope, None).await?;
+        Ok(Some(cached))
+    }
+
+    // for listing everything, a damaged record is skipped instead of failing the whole list
+    async fn read_all_entry<T: DeserializeOwned>(records: &Records, meta: &WeekMeta) -> Result<Option<T>, String> {
+        let Some(bytes) = records.get(WEEKS, &meta.key()).await? else {
+            return Ok(None);
+        };
+        Ok(decode(&bytes).unwrap_or_else(|e| {
+            log::warn!("Skipping damaged cached record {}: {}", meta.key(), e);
+            None
+        }))
+    }
+
+    async fn write_record<T: Serialize>(records: &Records, week: &Week, scope: Option<&str>, value: &T) -> Result<(), String> {
+        let bytes = encode(value)?;
+        records.put(WEEKS, &record_key(week, scope), &bytes).await?;
+        Self::touch(records, week, scope, Some(bytes.len() as u32)).await?;
+        Self::evict(records).await
+    }
+
+    // like write_record, but keeps the access time
+    async fn rewrite_record<T: Serialize>(records: &Records, mut meta: WeekMeta, value: &T) -> Result<(), String> {
+        let bytes = encode(value)?;
+        records.put(WEEKS, &meta.key(), &bytes).await?;
+        meta.size = bytes.len() as u32;
+        let meta_bytes = serde_json::to_vec(&meta).map_err(|e| e.to_string())?;
+        records.put(META, &meta.key(), &meta_bytes).await
+    }
+
+    async fn delete_record(records: &Records, key: &str) -> Result<(), String> {
+        for store in [WEEKS, META] {
+            records.delete(store, key).await?;
+        }
+        Ok(())
+    }
+
+    // updates the access time, and the size when the record was rewritten
+    async fn touch(records: &Records, week: &Week, scope: Option<&str>, size: Option<u32>) -> Result<(), String> {
+        let key = record_key(week, scope);
+        let size = match size {
+            Some(size) => size,
+            None => records
+                .get(META, &key)
+                .await?
+                .and_then(|b| serde_json::from_slice::<WeekMeta>(&b).ok())
+                .map(|m| m.size)
+                .unwrap_or(0),
+        };
+
+        let meta = WeekMeta {
+            week: week.clone(),
+            scope: scope.map(str::to_string),
+            size,
+            last_access: js_sys::Date::now(),
+        };
+        let bytes = serde_json::to_vec(&meta).map_err(|e| e.to_string())?;
+        records.put(META, &key, &bytes).await
+    }
+
+    // drops the least recently used records, the current and next week are always kept
+    async fn evict(records: &Records) -> Result<(), String> {
+        let mut meta = Self::all_meta(records).await?;
+        let mut total: u32 = meta.iter().map(|m| m.size).sum();
+        if total <= MAX_CACHE_BYTES {
+            return Ok(());
+        }
+
+        let keep = [Week::current(), Week::current_plus(1)];
+        meta.sort_by(|a, b| a.last_access.total_cmp(&b.last_access));
+        for m in meta.into_iter().filter(|m| !keep.contains(&m.week)) {
+            if total <= MAX_CACHE_BYTES {
+                break;
+            }
+            Self::delete_record(records, &m.key()).await?;
+            total -= m.size;
+        }
+        Ok(())
+    }
+
+    async fn all_meta(records: &Records) -> Result<Vec<WeekMeta>, String> {
+        Ok(records
+            .get_all(META)
+            .await?
+            .iter()
+            .filter_map(|b| serde_json::from_slice(b).ok())
+            .collect())
+    }
+
+    // native files in the tauri build, indexeddb in a plain browser
+    async fn records() -> Result<Records, String> {
+        if let Some(records) = RECORDS.with(|r| r.borrow().clone()) {
+            return Ok(records);
+        }
+
+        let records = if is_tauri() {
+            Records::Native
+        } else {
+            Records::IndexedDb(open_db().await?)
+        };
+        RECORDS.with(|r| *r.borrow_mut() = Some(records.clone()));
+
+        Self::migrate_legacy(&records).await;
+        Ok(records)
+    }
+
+    // every week of the old single-key cache becomes its own record, the key is only removed once all are written
+    async fn migrate_legacy(records: &Records) {
+        let Some(encoded) = PersistenceManager::get_legacy_timetables() else {
+            return;
+        };
+        let legacy = match decode_legacy_timetables(&encoded) {
+            Ok(legacy) => legacy,
+            Err(e) => {
+                log::warn!("Dropping the unreadable old timetable cache: {}", e);
+                PersistenceManager::remove_legacy_timetables();
+                return;
+            }
+        };
+
+        // how old the weeks are isn't known, so they are shown once and refetched right away
+        let now = Local::now().naive_local();
+        for (week, (_, tables)) in legacy.tables {
+            let cached = CachedWeek {
+                fetched: now,
+                expiry: Some(now),
+                tables: into_time_tables(tables),
+            };
+            if let Err(e) = Self::write_record(records, &week, None, &cached).await {
+                log::warn!("Failed to move the old timetable cache, trying again on the next start: {}", e);
+                return;
+            }
+        }
+        PersistenceManager::remove_legacy_timetables();
+    }
+}
+
+// whole weeks keep the plain week start as key, like before single timetables existed
+fn record_key(week: &Week, scope: Option<&str>) -> String {
+    match scope {
+        Some(scope) => format!("{}.{}", week.start, scope),
+        None => week.start.clone(),
+    }
+}
+
+#[derive(Clone)]
+enum Records {
+    IndexedDb(IdbDatabase),
+    Native, // records_* commands in src-tauri, values are base64 since they pass through json
+}
+
+#[derive(Serialize)]
+struct RecordArgs<'a> {
+    store: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+impl Records {
+    async fn get(&self, store: &str, key: &str) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            Records::IndexedDb(db) => {
+                let value = request(object_store(db, store, false)?.get(&key.into()).map_err(js_error)?).await?;
+                Ok((!value.is_undefined()).then(|| Uint8Array::new(&value).to_vec()))
+            }
+            Records::Native => {
+                let value = invoke_native("records_get", store, Some(key), None).await?;
+                Ok(value.as_string().and_then(|v| STANDARD.decode(v).ok()))
+            }
+        }
+    }
+
+    async fn put(&self, store: &str, key: &str, value: &[u8]) -> Result<(), String> {
+        match self {
+            Records::IndexedDb(db) => {
+                let req = object_store(db, store, true)?
+                    .put_with_key(&Uint8Array::from(value), &key.into())
+                    .map_err(js_error)?;
+                request(req).await.map(|_| ())
+            }
+            Records::Native => invoke_native("records_put", store, Some(key), Some(STANDARD.encode(value)))
+                .await
+                .map(|_| ()),
+        }
+    }
+
+    async fn delete(&self, store: &str, key: &str) -> Result<(), String> {
+        match self {
+            Records::IndexedDb(db) => {
+                let req = object_store(db, store, true)?.delete(&key.into()).map_err(js_error)?;
+                request(req).await.map(|_| ())
+            }
+            Records::Native => invoke_native("records_delete", store, Some(key), None).await.map(|_| ()),
+        }
+    }
+
+    async fn get_all(&self, store: &str) -> Result<Vec<Vec<u8>>, String> {
+        match self {
+            Records::IndexedDb(db) => {
+                let values = request(object_store(db, store, false)?.get_all().map_err(js_error)?).await?;
+                Ok(Array::from(&values).iter().map(|v| Uint8Array::new(&v).to_vec()).collect())
+            }
+            Records::Native => {
+                let values = invoke_native("records_get_all", store, None, None).await?;
+                Ok(Array::from(&values)
+                    .iter()
+                    .filter_map(|v| v.as_string())
+                    .filter_map(|v| STANDARD.decode(v).ok())
+                    .collect())
+            }
+        }
+    }
+
+    async fn clear(&self, store: &str) -> Result<(), String> {
+        match self {
+            Records::IndexedDb(db) => {
+                let req = object_store(db, store, true)?.clear().map_err(js_error)?;
+                request(req).await.map(|_| ())
+            }
+            Records::Native => invoke_native("records_clear", store, None, None).await.map(|_| ()),
+        }
+    }
+}
+
+async fn invoke_native(command: &str, store: &str, key: Option<&str>, value: Option<String>) -> Result<JsValue, String> {
+    let args = serde_wasm_bindgen::to_value(&RecordArgs { store, key, value }).map_err(|e| e.to_string())?;
+    try_invoke(command, args).await.map_err(js_error)
+}
+
+fn object_store(db: &IdbDatabase, store: &str, write: bool) -> Result<IdbObjectStore, String> {
+    let transaction = if write {
+        db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)
+    } else {
+        db.transaction_with_str(store)
+    };
+    transaction.and_then(|t| t.object_store(store)).map_err(js_error)
+}
+
+async fn open_db() -> Result<IdbDatabase, String> {
+    let factory = web_sys::window()
+        .ok_or("No global window found")?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or("IndexedDB is not available in this environment")?;
+    let open = factory.open_with_u32(DB_NAME, DB_VERSION).map_err(js_error)?;
+
+    let upgrading = open.clone();
+    let on_upgrade = Closure::once_into_js(move |_: Event| {
+        let Ok(db) = upgrading.result().map(|r| r.unchecked_into::<IdbDatabase>()) else {
+            return;
+        };
+        for name in [WEEKS, META] {
+            if !db.object_store_names().contains(name) {
+                let _ = db.create_object_store(name);
+            }
+        }
+    });
+    open.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    Ok(request(open.into()).await?.unchecked_into())
+}
+
+// resolves with the request's result once it succeeded
+async fn request(req: IdbRequest) -> Result<JsValue, String> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let succeeded = req.clone();
+        let on_success = Closure::once_into_js(move |_: Event| {
+            let _ = resolve.call1(&JsValue::NULL, &succeeded.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let failed = req.clone();
+        let on_error = Closure::once_into_js(move |_: Event| {
+            let message = failed
+                .error()
+                .ok()
+                .flatten()
+                .map(|e| e.message())
+                .unwrap_or("IndexedDB request failed".to_string());
+            let _ = reject.call1(&JsValue::NULL, &JsValue::from_str(&message));
+        });
+        req.set_onsuccess(Some(on_success.unchecked_ref()));
+        req.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let bytes = postcard::to_allocvec(value).map_err(|e| format!("Postcard failed: {}", e))?;
+    let compressed = zstd::encode_all(&bytes[..], 3).map_err(|e| format!("Compression failed: {}", e))?;
+    Ok([&[CACHE_FORMAT][..], &compressed[..]].concat())
+}
+
+// none for records of another cache format, an error for records of this one that don't decode
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<Option<T>, String> {
+    let Some((&CACHE_FORMAT, compressed)) = bytes.split_first() else {
+        return Ok(None);
+    };
+
+    let mut decompressed = Vec::new();
+    zstd::Decoder::new(compressed)
+        .and_then(|mut d| d.read_to_end(&mut decompressed))
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    postcard::from_bytes(&decompressed)
+        .map(Some)
+        .map_err(|e| format!("Postcard failed: {}", e))
+}
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
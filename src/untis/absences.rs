@@ -0,0 +1,137 @@
+use crate::data_models::clean_models::absences::Absence;
+use crate::data_models::clean_models::untis::{Entity, LessonBlock, ResourceType};
+use crate::data_models::response_models::untis_absences::UntisAbsencesResponse;
+use crate::data_models::response_models::untis_response_models::UntisAppData;
+use crate::errors::ApiError;
+use crate::persistence_manager::PersistenceManager;
+use crate::untis::auth::AuthHelper;
+use crate::untis::untis_client::UntisClient;
+use crate::untis::untis_week::Week;
+use chrono::{NaiveDate, NaiveDateTime};
+use futures::future::join_all;
+use std::collections::HashMap;
+
+pub struct AbsencesClient {
+    school_name: String,
+    untis_client: UntisClient,
+}
+
+impl AbsencesClient {
+    pub fn new() -> Result<Self, ApiError> {
+        let school_name = PersistenceManager::get_settings()?
+            .ok_or(ApiError::Authentication("Settings are empty".to_string()))?
+            .untis_auth
+            .school_identifier;
+
+        Ok(Self {
+            school_name,
+            untis_client: UntisClient::new()?,
+        })
+    }
+
+    async fn get_student_id(&self) -> Result<i64, ApiError> {
+        let url = format!(
+            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/app/data",
+            self.school_name
+        );
+
+        let response = AuthHelper::authorized_request("GET", &url, HashMap::new(), "".to_string()).await?;
+        let data: UntisAppData = serde_json::from_str(&response.body)
+            .map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+        // parents see their children in `students`, students only have their own person
+        data.user
+            .students
+            .first()
+            .or(data.user.person.as_ref())
+            .map(|p| p.id)
+            .ok_or(ApiError::Authentication("This account has no student attached".to_string()))
+    }
+
+    // the absences are complete, the missed lessons of weeks that couldn't be loaded are returned as warnings
+    pub async fn get_absences(&self, start: NaiveDate, end: NaiveDate) -> Result<(Vec<Absence>, Vec<String>), ApiError> {
+        let student_id = self.get_student_id().await?;
+        let url = format!(
+            "https://{}.webuntis.com/WebUntis/api/classreg/absences/students?startDate={}&endDate={}&studentId={}&excuseStatusId=-1",
+            self.school_name,
+            start.format("%Y%m%d"),
+            end.format("%Y%m%d"),
+            student_id,
+        );
+
+        let response = AuthHelper::authorized_request("GET", &url, HashMap::new(), "".to_string()).await?;
+        let mut absences = parse_absences(&response.body)?;
+        absences.sort_by_key(|a| std::cmp::Reverse(a.start));
+
+        let (timetable, warnings) = self.get_own_timetable(&absences, start, end).await?;
+        for absence in &mut absences {
+            absence.missed_subjects = missed_subjects(absence, &timetable);
+        }
+
+        Ok((absences, warnings))
+    }
+
+    // the student's own timetable, so group lessons they don't attend aren't counted.
+    // only the weeks of the school year that contain an absence are fetched, every request goes through the scheduler
+    async fn get_own_timetable(
+        &self,
+        absences: &[Absence],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<(Vec<LessonBlock>, Vec<String>), ApiError> {
+        if absences.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+
+        let Some(student) = self.untis_client.get_resources(ResourceType::Student, Week::current()).await?.into_iter().next() else {
+            return Ok((vec![], vec!["This account has no personal timetable, missed lessons can't be counted".to_string()]));
+        };
+
+        // an absence without a real date would otherwise reach back to 1970
+        let first = start.max(Week::school_year_start(end));
+        let mut weeks: Vec<Week> = absences
+            .iter()
+            .filter(|a| a.start != NaiveDateTime::default())
+            .flat_map(|a| Week::covering(a.start.date().max(first), a.end.date().min(end)))
+            .collect();
+        weeks.sort_by_key(|w| w.start.clone());
+        weeks.dedup();
+
+        let results = join_all(weeks.into_iter().map(|week| async {
+            let result = self.untis_client.get_days(week.clone(), ResourceType::Student, student.id).await;
+            (week, result)
+        }))
+        .await;
+
+        let mut lessons = Vec::new();
+        let mut warnings = Vec::new();
+        for (week, result) in results {
+            match result {
+                Ok(days) => lessons.extend(days.into_iter().flat_map(|d| d.lessons)),
+                Err(e) => warnings.push(format!("Could not get the timetable of the week from {}: {}", week.start, e)),
+            }
+        }
+        Ok((lessons, warnings))
+    }
+}
+
+pub fn parse_absences(body: &str) -> Result<Vec<Absence>, ApiError> {
+    let response: UntisAbsencesResponse = serde_json::from_str(body)
+        .map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+    Ok(response.data.absences.into_iter().map(Absence::from).collect())
+}
+
+fn missed_subjects(absence: &Absence, lessons: &[LessonBlock]) -> Vec<String> {
+    lessons
+        .iter()
+        .filter(|l| l.status != "CANCELLED")
+        .filter(|l| l.time_range.start < absence.end && absence.start < l.time_range.end)
+        .filter_map(|l| {
+            l.entities.iter().find_map(|e| match &e.inner {
+                Entity::Subject(s) => Some(s.short_name.clone()),
+                _ => None,
+            })
+        })
+        .collect()
+}
@@ -1,11 +1,36 @@
 use crate::errors::ApiError;
-use crate::persistence_manager::{Cookies, PersistenceManager};
+use crate::persistence_manager::{Cookies, PersistenceManager, UntisAuthMode};
 use crate::request_proxy::{request_proxy, ProxyResponse};
+use crate::untis::request_scheduler::RequestScheduler;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures::future::{LocalBoxFuture, Shared};
+use futures::FutureExt;
 use js_sys::Date;
 use serde_json::json;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use totp_rs::{Algorithm, Secret, TOTP};
 
+thread_local! {
+    // requests that find the session expired at the same time wait for a single login
+    static RENEWAL: RefCell<Option<Renewal>> = const { RefCell::new(None) };
+    // kept in memory only, a new one is cheap once the session is gone
+    static TOKEN: RefCell<Option<CachedToken>> = const { RefCell::new(None) };
+}
+
+// the login every waiting request shares
+type Renewal = Shared<LocalBoxFuture<'static, Result<(), String>>>;
+
+// tokens are fetched again this long before they expire
+const TOKEN_MARGIN_MS: f64 = 60_000.0;
+
+struct CachedToken {
+    session: String, // JSESSIONID the token was issued for
+    token: String,
+    expires: Option<f64>, // ms since epoch, none when the token doesn't say. it is then used until a 401
+}
+
 pub struct AuthHelper;
 
 impl AuthHelper {
@@ -17,11 +42,19 @@ impl AuthHelper {
         school_name: String,
         username: String,
         secret: String,
+        mode: UntisAuthMode,
     ) -> Result<(), ApiError> {
         if school_name.is_empty() || username.is_empty() || secret.is_empty() {
             return Err(ApiError::Authentication("Credentials not set".to_string()));
         }
 
+        match mode {
+            UntisAuthMode::Secret => Self::authenticate_otp(school_name, username, secret).await,
+            UntisAuthMode::Password => Self::authenticate_password(school_name, username, secret).await,
+        }
+    }
+
+    async fn authenticate_otp(school_name: String, username: String, secret: String) -> Result<(), ApiError> {
         let secret_bytes = Secret::Encoded(secret)
             .to_bytes()
             .map_err(|x| ApiError::Authentication(x.to_string()))?;
@@ -58,6 +91,49 @@ impl AuthHelper {
             )));
         }
 
+        Self::save_session_cookies(&response)?;
+        Ok(())
+    }
+
+    // the same form login as the webuntis website, answers with the same session cookies
+    async fn authenticate_password(school_name: String, username: String, password: String) -> Result<(), ApiError> {
+        let login_url = format!(
+            "https://{}.webuntis.com/WebUntis/j_spring_security_check",
+            school_name
+        );
+
+        let body = serde_urlencoded::to_string([
+            ("school", school_name.as_str()),
+            ("j_username", username.as_str()),
+            ("j_password", password.as_str()),
+            ("token", ""),
+        ])
+        .map_err(|e| ApiError::Miscellaneous(e.to_string()))?;
+
+        let headers = HashMap::from([
+            ("Content-Type".to_string(), vec!["application/x-www-form-urlencoded; charset=utf-8".to_string()]),
+            ("Accept".to_string(), vec!["application/json".to_string()]),
+        ]);
+
+        let response = request_proxy("POST", &login_url, headers, body)
+            .await
+            .map_err(|x| ApiError::Network(x.to_string()))?;
+
+        // failed logins still answer with 200 and name the reason in the body
+        if let Ok(response_json) = serde_json::from_str::<serde_json::Value>(&response.body)
+            && let Some(error) = response_json.get("loginError").and_then(|e| e.as_str())
+        {
+            return Err(ApiError::Authentication(error.to_string()));
+        }
+
+        if !Self::save_session_cookies(&response)? {
+            return Err(ApiError::Authentication("Login failed, check the username and password".to_string()));
+        }
+        Ok(())
+    }
+
+    // true when the response carried a complete session
+    fn save_session_cookies(response: &ProxyResponse) -> Result<bool, ApiError> {
         let mut jsessionid = None;
         let mut tenant_id = None;
         let mut school_name_cookie = None;
@@ -91,9 +167,10 @@ impl AuthHelper {
                 school_name_base32: school_name_cookie,
             };
             PersistenceManager::save_cookies(&cookies).map_err(ApiError::Miscellaneous)?;
+            return Ok(true);
         }
 
-        Ok(())
+        Ok(false)
     }
 
     async fn get_token() -> Result<String, ApiError> {
@@ -106,11 +183,21 @@ impl AuthHelper {
 
         if settings.untis_auth.school_identifier.is_empty()
             || settings.untis_auth.user_identifier.is_empty()
-            || settings.untis_auth.secret.is_empty()
+            || settings.untis_auth.secret_handle.is_empty()
         {
             return Err(ApiError::Authentication("Credentials not set".to_string()));
         }
 
+        let cached = TOKEN.with(|t| {
+            t.borrow()
+                .as_ref()
+                .filter(|c| c.session == cookies.jsessionid && c.expires.is_none_or(|e| is_fresh(Date::now(), e)))
+                .map(|c| c.token.clone())
+        });
+        if let Some(token) = cached {
+            return Ok(token);
+        }
+
         let url = format!(
             "https://{}.webuntis.com/WebUntis/api/token/new",
             settings.untis_auth.school_identifier
@@ -119,12 +206,50 @@ impl AuthHelper {
         let mut headers = HashMap::new();
         headers.insert("Cookie".to_string(), vec![cookies.to_header_value()]);
 
-        Ok(request_proxy("GET", url.as_str(), headers, "".to_string())
-            .await?
-            .body)
+        // an expired session answers with the login page instead of a token
+        let token = request_proxy("GET", url.as_str(), headers, "".to_string())
+            .await
+            .map_err(ApiError::Network)?
+            .body;
+        let Some(expires) = jwt_expiry(&token) else {
+            return Err(ApiError::Authentication("Session expired".to_string()));
+        };
+
+        TOKEN.with(|t| {
+            *t.borrow_mut() = Some(CachedToken {
+                session: cookies.jsessionid,
+                token: token.clone(),
+                expires,
+            })
+        });
+        Ok(token)
     }
 
+    // goes through the scheduler, and renews the session once and retries when it turns out to be expired
     pub async fn authorized_request(
+        method: &str,
+        url: &str,
+        headers: HashMap<String, Vec<String>>,
+        body: String,
+    ) -> Result<ProxyResponse, ApiError> {
+        // owned, a request that timed out keeps running after this returns
+        let (method, url) = (method.to_string(), url.to_string());
+        let send = || {
+            let (method, url, headers, body) = (method.clone(), url.clone(), headers.clone(), body.clone());
+            async move { Self::send_authorized(&method, &url, headers, body).await }
+        };
+        match RequestScheduler::send(send).await {
+            Err(ApiError::Authentication(reason)) => {
+                TOKEN.with(|t| t.borrow_mut().take());
+                log::info!("Renewing the Untis session: {}", reason);
+                Self::renew_session().await?;
+                RequestScheduler::send(send).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send_authorized(
         method: &str,
         url: &str,
         mut headers: HashMap<String, Vec<String>>,
@@ -135,6 +260,103 @@ impl AuthHelper {
             "Authorization".to_string(),
             vec![format!("Bearer {}", token)],
         );
-        Ok(request_proxy(method, url, headers, body).await?)
+
+        let response = request_proxy(method, url, headers, body).await.map_err(ApiError::Network)?;
+        // only a 401 means the session is gone, a 403 is a view the account may not open and a new login won't change that
+        match response.status {
+            401 => Err(ApiError::Authentication("Request rejected with status 401".to_string())),
+            403 => Err(ApiError::Permission("Request rejected with status 403".to_string())),
+            _ => Ok(response),
+        }
+    }
+
+    async fn renew_session() -> Result<(), ApiError> {
+        let renewal = RENEWAL.with(|r| {
+            r.borrow_mut()
+                .get_or_insert_with(|| Self::login_from_settings().boxed_local().shared())
+                .clone()
+        });
+        let result = renewal.await;
+        RENEWAL.with(|r| r.borrow_mut().take());
+        result.map_err(ApiError::Authentication)
+    }
+
+    async fn login_from_settings() -> Result<(), String> {
+        let auth = PersistenceManager::get_settings()?
+            .ok_or("Settings are empty")?
+            .untis_auth;
+        let secret = auth.secret().await?;
+        Self::authenticate(auth.school_identifier, auth.user_identifier, secret, auth.mode)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+// the exp claim in ms since epoch. none for anything that isn't a jwt, an inner none for a jwt without exp claim
+fn jwt_expiry(token: &str) -> Option<Option<f64>> {
+    let mut parts = token.trim().split('.');
+    let (_, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    if !claims.is_object() {
+        return None;
+    }
+    Some(claims.get("exp").and_then(|exp| exp.as_f64()).map(|exp| exp * 1000.0))
+}
+
+// whether a token expiring at expires (ms since epoch) may still be used at now
+fn is_fresh(now: f64, expires: f64) -> bool {
+    now < expires - TOKEN_MARGIN_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        format!(
+            "{}.{}.{}",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims),
+            URL_SAFE_NO_PAD.encode("signature")
+        )
+    }
+
+    #[test]
+    fn reads_the_expiry_in_ms() {
+        assert_eq!(jwt_expiry(&jwt(r#"{"sub":"jdoe","exp":1700000000}"#)), Some(Some(1_700_000_000_000.0)));
+        assert_eq!(jwt_expiry(&format!("  {}\n", jwt(r#"{"exp":1700000000}"#))), Some(Some(1_700_000_000_000.0)));
+    }
+
+    #[test]
+    fn rejects_anything_but_a_jwt() {
+        let login_page = "<!DOCTYPE html><html><head><title>WebUntis</title></head><body>Login</body></html>";
+        assert_eq!(jwt_expiry(login_page), None);
+        assert_eq!(jwt_expiry(""), None);
+
+        let token = jwt(r#"{"exp":1700000000}"#);
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(jwt_expiry(&parts[..2].join(".")), None);
+        assert_eq!(jwt_expiry(&format!("{}.extra", token)), None);
+    }
+
+    #[test]
+    fn no_expiry_without_exp_claim() {
+        // still a valid token, only its lifetime is unknown
+        assert_eq!(jwt_expiry(&jwt(r#"{"sub":"jdoe"}"#)), Some(None));
+        assert_eq!(jwt_expiry(&jwt("not json")), None);
+        assert_eq!(jwt_expiry(&jwt("42")), None);
+    }
+
+    #[test]
+    fn refreshes_within_the_margin() {
+        let expires = 1_700_000_000_000.0;
+        assert!(is_fresh(expires - TOKEN_MARGIN_MS - 1.0, expires));
+        assert!(!is_fresh(expires - TOKEN_MARGIN_MS, expires));
+        assert!(!is_fresh(expires + 1.0, expires));
     }
 }
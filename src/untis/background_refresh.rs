@@ -0,0 +1,57 @@
+use crate::persistence_manager::PersistenceManager;
+use crate::untis::cached_untis_client::CachedUntisClient;
+use crate::untis::untis_client::UntisClient;
+use crate::untis::untis_week::Week;
+use chrono::{Duration, Local, NaiveDateTime, Timelike};
+use gloo_timers::callback::Interval;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+// checks once a minute so changed settings apply without restarting the scheduler
+const TICK_MS: u32 = 60_000;
+
+// refreshes the current and next week in the configured interval, `on_refreshed` fires when new data was fetched.
+// dropping the returned interval stops the scheduler
+pub fn start_background_refresh(on_refreshed: Callback<()>) -> Interval {
+    let last_run: Rc<RefCell<Option<NaiveDateTime>>> = Rc::new(RefCell::new(None));
+    let running = Rc::new(Cell::new(false));
+
+    Interval::new(TICK_MS, move || {
+        let Some(settings) = PersistenceManager::get_settings().ok().flatten().map(|s| s.refresh_settings) else {
+            return;
+        };
+        let now = Local::now().naive_local();
+        let interval = Duration::minutes(settings.interval_minutes.max(1) as i64);
+
+        if !settings.enabled || settings.is_quiet(now.hour()) || running.get() || !UntisClient::is_authenticated() {
+            return;
+        }
+        if last_run.borrow().is_some_and(|last| now - last < interval) {
+            return;
+        }
+
+        *last_run.borrow_mut() = Some(now);
+        running.set(true);
+
+        let running = running.clone();
+        let on_refreshed = on_refreshed.clone();
+        spawn_local(async move {
+            let mut refreshed = false;
+            if let Ok(client) = CachedUntisClient::new() {
+                for week in [Week::current(), Week::current_plus(1)] {
+                    match client.refresh_week(week).await {
+                        Ok(r) => refreshed |= r,
+                        Err(e) => log::warn!("Background refresh failed: {}", e),
+                    }
+                }
+            }
+
+            running.set(false);
+            if refreshed {
+                on_refreshed.emit(());
+            }
+        });
+    })
+}
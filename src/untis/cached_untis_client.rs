@@ -1,63 +1,470 @@
-use crate::data_models::clean_models::untis::{Entity, WeekTimeTable};
+use crate::data_models::clean_models::changes::TimetableChange;
+use crate::data_models::clean_models::untis::{Entity, MasterData, Resource, ResourceType, WeekTimeTable};
 use crate::errors::ApiError;
-use crate::persistence_manager::{PersistenceManager, TimeTableCache, TimeTables};
-use crate::untis::teacher_table_generator::gen_all_timetables;
-use crate::untis::untis_client::UntisClient;
+use crate::notifications::notify;
+use crate::persistence_manager::{CachedTable, CachedWeek, PersistenceManager, TimeTables};
+use crate::timetable_store::TimetableStore;
+use crate::untis::ics_export::{school_timezone, to_ics};
+use crate::untis::ics_feed::schedule_publish;
+use crate::untis::timetable_diff::{diff_table, diff_timetables};
+use crate::untis::teacher_table_generator::{add_free_entities, gen_all_timetables};
+use crate::untis::untis_client::{CrawlProgress, UntisClient};
 use crate::untis::untis_week::Week;
-use chrono::Local;
-use std::collections::HashMap;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+// how long a fetched week stays fresh depends on how likely it still changes
+const SHORT_TTL_MINUTES: i64 = 15; // current and next week
+const MEDIUM_TTL_HOURS: i64 = 6; // weeks further ahead
+const LONG_TTL_DAYS: i64 = 7; // recent past weeks
+const FROZEN_AFTER_WEEKS: i64 = 4; // older weeks are never refetched
+const PARTIAL_TTL_MINUTES: i64 = 2; // weeks where some classes failed
+
+// detected changes are kept until dismissed, but no longer than this
+const KEEP_CHANGES_DAYS: i64 = 14;
+const MAX_STORED_CHANGES: usize = 500;
+
+// a week of the class crawl, or a week of a single resource
+type CacheKey = (Week, Option<String>);
+
+thread_local! {
+    // records with a background revalidation in flight, so repeated reads don't start another one
+    static REVALIDATING: RefCell<HashSet<CacheKey>> = RefCell::new(HashSet::new());
+    // the lists of classes, teachers and rooms rarely change, they are fetched once per session
+    static RESOURCES: RefCell<HashMap<ResourceType, Vec<Resource>>> = RefCell::new(HashMap::new());
+    static OWN_CLASS: Cell<Option<i32>> = const { Cell::new(None) };
+    static MASTER_DATA: RefCell<Option<MasterData>> = const { RefCell::new(None) };
+    // classes that failed in the last crawl of a week
+    static WARNINGS: RefCell<HashMap<Week, Vec<String>>> = RefCell::new(HashMap::new());
+}
 
 pub struct CachedUntisClient {
     untis_client: UntisClient,
+    on_revalidated: Option<Callback<()>>,
 }
 
 impl CachedUntisClient {
     pub fn new() -> Result<Self, ApiError> {
         Ok(Self {
             untis_client: UntisClient::new()?,
+            on_revalidated: None,
         })
     }
 
+    // called once a stale week served from the cache has been refetched
+    pub fn with_revalidation_callback(mut self, callback: Callback<()>) -> Self {
+        self.on_revalidated = Some(callback);
+        self
+    }
+
+    // false unless the user opted into deriving teacher and room timetables from all classes
+    pub fn crawls_all_classes() -> bool {
+        PersistenceManager::get_settings()
+            .ok()
+            .flatten()
+            .is_some_and(|s| s.timetable_settings.crawl_all_classes)
+    }
+
     pub async fn get_class_timetables(&self, week: Week) -> Result<TimeTables, ApiError> {
-        let cache = PersistenceManager::get_timetables()?;
-        let mut cache_table = HashMap::new();
-        if let Some(mut c) = cache {
-            if let Some((e, t)) = c.tables.get(&week) {
-                if e.is_none() {
-                    return Ok(t.clone());
+        match self.cached_class_timetables(&week).await {
+            Some(tables) => Ok(tables),
+            None => self.fetch_week(week, None, None).await,
+        }
+    }
+
+    // like get_all_timetables, but an uncached week is crawled in the background instead of waited for.
+    // every finished class is reported to on_progress, on_revalidated fires once the whole week is cached
+    pub async fn get_all_timetables_progressive(
+        &self,
+        week: Week,
+        on_progress: Callback<(Week, Result<CrawlProgress, String>)>,
+    ) -> Result<Option<(HashMap<Entity, WeekTimeTable>, Option<i32>)>, ApiError> {
+        if let Some(tables) = self.cached_class_timetables(&week).await {
+            return self.gen_with_master_data(tables).await.map(Some);
+        }
+
+        let crawled = week.clone();
+        let progress = {
+            let week = week.clone();
+            on_progress.reform(move |p| (week.clone(), Ok(p)))
+        };
+        self.revalidate((week, None), async move {
+            let result = CachedUntisClient::new()?.fetch_week(crawled.clone(), None, Some(progress)).await;
+            if let Err(e) = &result {
+                on_progress.emit((crawled, Err(e.to_string())));
+            }
+            result.map(|_| ())
+        });
+        Ok(None)
+    }
+
+    // stale weeks are served right away while they are refetched in the background
+    async fn cached_class_timetables(&self, week: &Week) -> Option<TimeTables> {
+        let cached = cached_week(week).await?;
+
+        if is_expired(cached.expiry) {
+            let previous = cached.tables.clone();
+            let stale = week.clone();
+            self.revalidate((week.clone(), None), async move {
+                CachedUntisClient::new()?.fetch_week(stale, Some(previous), None).await.map(|_| ())
+            });
+        }
+
+        Some(cached.tables)
+    }
+
+    pub async fn get_resources(&self, kind: ResourceType) -> Result<Vec<Resource>, ApiError> {
+        if let Some(resources) = RESOURCES.with(|r| r.borrow().get(&kind).cloned()) {
+            return Ok(resources);
+        }
+
+        let fetched = if kind == ResourceType::Class {
+            self.untis_client
+                .get_class_filter(Week::current())
+                .await
+                .map(|(classes, own_class, master)| {
+                    OWN_CLASS.with(|o| o.set(own_class));
+                    MASTER_DATA.with(|m| *m.borrow_mut() = Some(master));
+                    classes.into_iter().map(Resource::from).collect()
+                })
+        } else {
+            self.untis_client
+                .get_resources(kind, Week::current())
+                .await
+                .map_err(|e| direct_view_error(kind, e))
+        };
+
+        let mut known = PersistenceManager::get_known_resources().unwrap_or_default();
+        let resources = match fetched {
+            Ok(resources) => {
+                known.resources.insert(kind, resources.clone());
+                if kind == ResourceType::Class {
+                    known.own_class = OWN_CLASS.with(|o| o.get());
+                }
+                if let Err(e) = PersistenceManager::save_known_resources(&known) {
+                    log::warn!("Failed to save the resource lists: {}", e);
+                }
+                resources
+            }
+            // offline or without a session, the cached timetables can still be opened with the last known lists
+            Err(e) => {
+                let Some(resources) = known.resources.remove(&kind) else {
+                    return Err(e);
+                };
+                log::info!("Using the last known {} list: {}", kind.as_param().to_lowercase(), e);
+                if kind == ResourceType::Class {
+                    OWN_CLASS.with(|o| o.set(known.own_class));
                 }
-                if e.unwrap() > Local::now().naive_local() {
-                    return Ok(t.clone());
-                } else {
-                    c.tables.remove(&week);
+                resources
+            }
+        };
+
+        RESOURCES.with(|r| r.borrow_mut().insert(kind, resources.clone()));
+        Ok(resources)
+    }
+
+    // comes with the class list, lessons only carry the short names.
+    // empty when that couldn't be fetched, the lessons then keep their names
+    async fn master_data(&self) -> MasterData {
+        if let Err(e) = self.get_resources(ResourceType::Class).await {
+            log::warn!("Failed to get the master data: {}", e);
+        }
+        MASTER_DATA.with(|m| m.borrow().clone()).unwrap_or_default()
+    }
+
+    // the class webuntis pre-selects for the account
+    pub async fn own_class(&self) -> Result<Option<i32>, ApiError> {
+        self.get_resources(ResourceType::Class).await?;
+        Ok(OWN_CLASS.with(|o| o.get()))
+    }
+
+    // the direct counterpart to get_class_timetables, only this resource's timetable is fetched
+    pub async fn get_resource_timetable(&self, week: Week, resource: &Resource) -> Result<WeekTimeTable, ApiError> {
+        let Some(cached) = cached_table(&week, resource).await else {
+            return self.fetch_table(week, resource.clone(), None).await;
+        };
+
+        if is_expired(cached.expiry) {
+            let previous = cached.table.clone();
+            let stale = week.clone();
+            let resource = resource.clone();
+            let key = (week, Some(resource.key()));
+            self.revalidate(key, async move {
+                CachedUntisClient::new()?.fetch_table(stale, resource, Some(previous)).await.map(|_| ())
+            });
+        }
+
+        Ok(cached.table)
+    }
+
+    // used by the background refresh, records that haven't expired yet are left alone.
+    // without the crawl only the timetables that were looked at this week are refreshed
+    pub async fn refresh_week(&self, week: Week) -> Result<bool, ApiError> {
+        if !Self::crawls_all_classes() {
+            let mut refreshed = false;
+            for (_, cached) in TimetableStore::all_tables().await?.into_iter().filter(|(w, _)| *w == week) {
+                if is_expired(cached.expiry) {
+                    self.fetch_table(week.clone(), cached.resource, Some(cached.table)).await?;
+                    refreshed = true;
                 }
             }
-            cache_table = c.tables.clone();
+            return Ok(refreshed);
         }
 
-        let tt = self
+        let previous = match cached_week(&week).await {
+            Some(cached) if !is_expired(cached.expiry) => return Ok(false),
+            Some(cached) => Some(cached.tables),
+            None => None,
+        };
+
+        self.fetch_week(week, previous, None).await?;
+        Ok(true)
+    }
+
+    pub async fn last_updated(week: &Week) -> Option<NaiveDateTime> {
+        cached_week(week).await.map(|c| c.fetched)
+    }
+
+    // empty unless the last crawl of the week only got some of the classes
+    pub fn warnings(week: &Week) -> Vec<String> {
+        WARNINGS.with(|w| w.borrow().get(week).cloned().unwrap_or_default())
+    }
+
+    pub async fn table_last_updated(week: &Week, resource: &Resource) -> Option<NaiveDateTime> {
+        cached_table(week, resource).await.map(|c| c.fetched)
+    }
+
+    async fn fetch_week(
+        &self,
+        week: Week,
+        previous: Option<TimeTables>,
+        on_progress: Option<Callback<CrawlProgress>>,
+    ) -> Result<TimeTables, ApiError> {
+        let (mut tt, warnings) = self
             .untis_client
-            .get_all_class_timetables(week.clone())
+            .get_all_class_timetables(week.clone(), on_progress)
             .await?;
-        cache_table.insert(week, (None, tt.clone()));
-        PersistenceManager::save_timetables(&TimeTableCache {
-            tables: cache_table,
-        })?;
+        let master = self.master_data().await;
+        for table in tt.0.values_mut() {
+            table.resolve_entities(&master);
+        }
+
+        // failed classes keep what was cached for them before
+        if let Some(previous) = previous.as_ref().filter(|_| !warnings.is_empty()) {
+            for (class, table) in &previous.0 {
+                tt.0.entry(class.clone()).or_insert_with(|| table.clone());
+            }
+        }
+
+        // an incomplete week is kept, but fetched again soon
+        let fetched = Local::now().naive_local();
+        let expiry = if warnings.is_empty() {
+            expiry_for(&week, fetched)
+        } else {
+            Some(fetched + Duration::minutes(PARTIAL_TTL_MINUTES))
+        };
+        TimetableStore::put_week(&week, &CachedWeek {
+            fetched,
+            expiry,
+            tables: tt.clone(),
+        })
+        .await?;
+
+        // the missing classes would show up as cancelled lessons of their teachers and rooms
+        let complete = warnings.is_empty();
+        WARNINGS.with(|w| w.borrow_mut().insert(week.clone(), warnings));
+
+        if let Some(previous) = previous
+            && complete {
+            match diff_timetables(&previous, &tt) {
+                Ok(changes) => record_changes(changes, tt.1).await,
+                Err(e) => log::warn!("Failed to compare timetables: {}", e),
+            }
+        }
+
+        schedule_publish();
         Ok(tt)
     }
 
+    async fn fetch_table(&self, week: Week, resource: Resource, previous: Option<WeekTimeTable>) -> Result<WeekTimeTable, ApiError> {
+        let own_class = self.own_class().await?;
+        let mut table = self
+            .untis_client
+            .get_resource_timetable(week.clone(), &resource, own_class)
+            .await
+            .map_err(|e| direct_view_error(resource.kind, e))?;
+        table.resolve_entities(&self.master_data().await);
+
+        let fetched = Local::now().naive_local();
+        TimetableStore::put_table(&week, &CachedTable {
+            resource: resource.clone(),
+            fetched,
+            expiry: expiry_for(&week, fetched),
+            table: table.clone(),
+        })
+        .await?;
+
+        if let Some(previous) = previous {
+            record_changes(diff_table(&resource.entity, &previous, &table), own_class).await;
+        }
+
+        schedule_publish();
+        Ok(table)
+    }
+
+    // refetches in the background unless that is already happening for the record
+    fn revalidate(&self, key: CacheKey, fetch: impl Future<Output = Result<(), ApiError>> + 'static) {
+        if !REVALIDATING.with(|r| r.borrow_mut().insert(key.clone())) {
+            return;
+        }
+
+        let on_revalidated = self.on_revalidated.clone();
+        spawn_local(async move {
+            let result = fetch.await;
+            REVALIDATING.with(|r| r.borrow_mut().remove(&key));
+
+            match (result, on_revalidated) {
+                (Ok(_), Some(callback)) => callback.emit(()),
+                (Err(e), _) => log::warn!("Failed to revalidate {}: {}", key.0.start, e),
+                _ => {}
+            }
+        });
+    }
+
     pub async fn get_all_timetables(
         &self,
         week: Week,
     ) -> Result<(HashMap<Entity, WeekTimeTable>, Option<i32>), ApiError> {
         let r = self.get_class_timetables(week).await?;
-        gen_all_timetables(r.0, r.1)
+        self.gen_with_master_data(r).await
+    }
+
+    // weeks cached while the master data couldn't be fetched only name their teachers and rooms,
+    // they are resolved again now that it may be known
+    async fn gen_with_master_data(&self, mut tables: TimeTables) -> Result<(HashMap<Entity, WeekTimeTable>, Option<i32>), ApiError> {
+        let master = self.master_data().await;
+        for table in tables.0.values_mut() {
+            table.resolve_entities(&master);
+        }
+        let (mut all, own_class) = gen_all_timetables(tables.0, tables.1)?;
+        add_free_entities(&mut all, &master);
+        Ok((all, own_class))
+    }
+
+    pub async fn export_ics(&self, entity: &Entity, weeks: Vec<Week>) -> Result<String, ApiError> {
+        let mut tables = Vec::new();
+        for week in weeks {
+            let (mut all, _) = self.get_all_timetables(week).await?;
+            if let Some(table) = all.remove(entity) {
+                tables.push(table);
+            }
+        }
+        Ok(to_ics(entity, &tables, school_timezone()))
     }
 
-    pub fn clear_cache() -> Result<(), ApiError> {
-        PersistenceManager::save_timetables(&TimeTableCache {
-            tables: HashMap::new(),
+    pub async fn export_resource_ics(&self, resource: &Resource, weeks: Vec<Week>) -> Result<String, ApiError> {
+        let mut tables = Vec::new();
+        for week in weeks {
+            tables.push(self.get_resource_timetable(week, resource).await?);
+        }
+        Ok(to_ics(&resource.entity, &tables, school_timezone()))
+    }
+
+    // marks every cached record as expired, the next read refetches and diffs it
+    pub async fn invalidate_cache() -> Result<(), ApiError> {
+        RESOURCES.with(|r| r.borrow_mut().clear());
+        MASTER_DATA.with(|m| *m.borrow_mut() = None);
+
+        TimetableStore::expire_all(Local::now().naive_local()).await?;
+        Ok(())
+    }
+}
+
+// webuntis rejects views the account has no permission for, the crawl is the way around that
+fn direct_view_error(kind: ResourceType, e: ApiError) -> ApiError {
+    match (kind, e) {
+        (ResourceType::Teacher | ResourceType::Room, ApiError::Permission(reason)) => ApiError::Permission(format!(
+            "Your account can't open {} timetables directly ({}). Enable \"Reconstruct from all classes\" in the settings.",
+            kind.as_param().to_lowercase(),
+            reason
+        )),
+        (_, e) => e,
+    }
+}
+
+// an unreadable cache is treated like an empty one and simply refetched
+async fn cached_week(week: &Week) -> Option<CachedWeek> {
+    TimetableStore::get_week(week).await.unwrap_or_else(|e| {
+        log::warn!("Failed to read the timetable cache: {}", e);
+        None
+    })
+}
+
+async fn cached_table(week: &Week, resource: &Resource) -> Option<CachedTable> {
+    TimetableStore::get_table(week, resource).await.unwrap_or_else(|e| {
+        log::warn!("Failed to read the timetable cache: {}", e);
+        None
+    })
+}
+
+fn is_expired(expiry: Option<NaiveDateTime>) -> bool {
+    expiry.is_some_and(|e| e <= Local::now().naive_local())
+}
+
+fn expiry_for(week: &Week, fetched: NaiveDateTime) -> Option<NaiveDateTime> {
+    let current = Week::current();
+    let start = week.start_date();
+
+    if week.end_date() < current.start_date() - Duration::weeks(FROZEN_AFTER_WEEKS) {
+        None
+    } else if start < current.start_date() {
+        Some(fetched + Duration::days(LONG_TTL_DAYS))
+    } else if start <= current.next().start_date() {
+        Some(fetched + Duration::minutes(SHORT_TTL_MINUTES))
+    } else {
+        Some(fetched + Duration::hours(MEDIUM_TTL_HOURS))
+    }
+}
+
+// stores the changes for the "what changed" panel and notifies about the user's own class and timetable
+async fn record_changes(changes: Vec<TimetableChange>, own_class: Option<i32>) {
+    if changes.is_empty() {
+        return;
+    }
+
+    let own: Vec<_> = changes
+        .iter()
+        .filter(|c| match &c.entity {
+            Entity::Class(class) => Some(class.id) == own_class,
+            Entity::Student(_) => true,
+            _ => false,
         })
-        .map_err(ApiError::from)
+        .collect();
+
+    // every revalidation adds to the list, so old and surplus changes are dropped
+    let cutoff = Local::now().naive_local() - Duration::days(KEEP_CHANGES_DAYS);
+    let mut stored = PersistenceManager::get_changes().unwrap_or_default();
+    stored.extend(changes.iter().cloned());
+    stored.retain(|c| c.detected >= cutoff && c.time_range.end >= cutoff);
+    if stored.len() > MAX_STORED_CHANGES {
+        stored.drain(..stored.len() - MAX_STORED_CHANGES);
+    }
+    if let Err(e) = PersistenceManager::save_changes(&stored) {
+        log::warn!("Failed to save timetable changes: {}", e);
+    }
+
+    if own.is_empty() {
+        return;
+    }
+    let mut body = own.iter().take(3).map(|c| c.describe()).collect::<Vec<_>>().join("\n");
+    if own.len() > 3 {
+        body.push_str(&format!("\nand {} more", own.len() - 3));
+    }
+    if let Err(e) = notify("Timetable changed", &body).await {
+        log::warn!("Failed to send notification: {}", e);
     }
 }
@@ -0,0 +1,197 @@
+use crate::data_models::clean_models::untis::{ChangeStatus, Entity, LessonBlock, WeekTimeTable};
+use crate::persistence_manager::{PersistenceManager, DEFAULT_SCHOOL_TIMEZONE};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+// the configured zone, or the default one if it doesn't parse
+pub fn school_timezone() -> Tz {
+    let name = PersistenceManager::get_settings()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .timetable_settings
+        .school_timezone;
+    name.parse().unwrap_or_else(|_| {
+        log::warn!("Unknown school timezone {}", name);
+        DEFAULT_SCHOOL_TIMEZONE.parse().unwrap_or(Tz::UTC)
+    })
+}
+
+// RFC 5545 calendar with one VEVENT per lesson the entity takes part in
+pub fn to_ics(entity: &Entity, weeks: &[WeekTimeTable], tz: Tz) -> String {
+    let stamp = format_utc(Utc::now().naive_utc());
+    let slug = entity_slug(entity);
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Altis//Untis Timetable//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&entity.name())),
+    ];
+
+    // joint lessons show up once per class in derived tables, their copies are merged into one event
+    let mut lessons: Vec<(String, LessonBlock)> = Vec::new();
+    let mut by_uid: HashMap<String, usize> = HashMap::new();
+    for day in weeks.iter().flat_map(|w| &w.days) {
+        for lesson in day.lessons.iter().filter(|l| l.r#type != "Break") {
+            let uid = lesson_uid(&slug, lesson);
+            match by_uid.get(&uid) {
+                Some(&i) => {
+                    let merged = &mut lessons[i].1;
+                    for e in &lesson.entities {
+                        if !merged.entities.contains(e) {
+                            merged.entities.push(e.clone());
+                        }
+                    }
+                }
+                None => {
+                    by_uid.insert(uid.clone(), lessons.len());
+                    lessons.push((uid, lesson.clone()));
+                }
+            }
+        }
+    }
+    for (uid, lesson) in &lessons {
+        lines.extend(to_vevent(entity, uid, lesson, &stamp, tz));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+fn to_vevent(entity: &Entity, uid: &str, lesson: &LessonBlock, stamp: &str, tz: Tz) -> Vec<String> {
+    let names = |filter: fn(&Entity) -> bool| {
+        lesson
+            .entities
+            .iter()
+            .filter(|e| e.status != ChangeStatus::Removed && filter(&e.inner))
+            .map(|e| e.inner.long_name())
+            .collect::<Vec<_>>()
+    };
+
+    let summary = lesson
+        .entities
+        .iter()
+        .filter(|e| e.status != ChangeStatus::Removed)
+        .find_map(|e| match &e.inner {
+            Entity::Subject(s) if !s.long_name.is_empty() => Some(s.long_name.clone()),
+            Entity::Subject(s) => Some(s.short_name.clone()),
+            _ => None,
+        })
+        .or_else(|| lesson.exam.as_ref().map(|e| e.name.clone()))
+        .unwrap_or_else(|| lesson.r#type.clone());
+
+    // the entity itself being removed from a lesson (e.g. a substituted teacher) cancels it for this calendar
+    let removed_for_entity = lesson
+        .entities
+        .iter()
+        .any(|e| e.status == ChangeStatus::Removed && e.inner == *entity);
+    let changed = lesson.status == "CHANGED" || lesson.entities.iter().any(|e| e.status != ChangeStatus::Regular);
+    let status = if lesson.status == "CANCELLED" || removed_for_entity { "CANCELLED" } else { "CONFIRMED" };
+
+    let mut description = Vec::new();
+    let teachers = names(|e| matches!(e, Entity::Teacher(_)));
+    if !teachers.is_empty() {
+        description.push(format!("Teachers: {}", teachers.join(", ")));
+    }
+    let classes = names(|e| matches!(e, Entity::Class(_)));
+    if !classes.is_empty() {
+        description.push(format!("Classes: {}", classes.join(", ")));
+    }
+    if let Some(exam) = &lesson.exam {
+        description.push(format!("Exam: {} {}", exam.exam_type, exam.name).trim().to_string());
+    }
+    for texts in &lesson.texts {
+        let mut texts: Vec<_> = texts.iter().filter(|(_, v)| !v.is_empty()).collect();
+        texts.sort();
+        description.extend(texts.into_iter().map(|(k, v)| format!("{}: {}", k, v)));
+    }
+    if changed && status != "CANCELLED" {
+        description.push("Changed".to_string());
+    }
+
+    let mut event = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", stamp),
+        format!("DTSTART:{}", format_local(lesson.time_range.start, tz)),
+        format!("DTEND:{}", format_local(lesson.time_range.end, tz)),
+        format!("SUMMARY:{}", escape(&summary)),
+        format!("STATUS:{}", status),
+    ];
+
+    let rooms = names(|e| matches!(e, Entity::Room(_)));
+    if !rooms.is_empty() {
+        event.push(format!("LOCATION:{}", escape(&rooms.join(", "))));
+    }
+    if !description.is_empty() {
+        event.push(format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+    }
+    if !lesson.link.is_empty() {
+        event.push(format!("URL:{}", lesson.link));
+    }
+
+    event.push("END:VEVENT".to_string());
+    event
+}
+
+// unique per entity, so the same lesson in the calendars of its class and its teacher doesn't clash
+fn lesson_uid(slug: &str, lesson: &LessonBlock) -> String {
+    if lesson.ids.is_empty() {
+        // blocks we generated ourselves, like exams outside of lessons
+        let exam_id = lesson.exam.as_ref().map(|e| e.id).unwrap_or_default();
+        format!("exam-{}-{}@{}.altis", exam_id, lesson.time_range.start.format("%Y%m%dT%H%M"), slug)
+    } else {
+        let ids = lesson.ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join("-");
+        format!("{}@{}.altis", ids, slug)
+    }
+}
+
+fn entity_slug(entity: &Entity) -> String {
+    let name: String = entity
+        .name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    format!("{}-{}", entity.category(), name)
+}
+
+// untis times are the school's wall clock, calendars get them as utc so no VTIMEZONE is needed
+fn format_local(time: NaiveDateTime, tz: Tz) -> String {
+    let utc = tz
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|t| t.naive_utc())
+        .unwrap_or(time);
+    format_utc(utc)
+}
+
+fn format_utc(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// content lines must not exceed 75 octets, continuations start with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
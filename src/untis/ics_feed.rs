@@ -0,0 +1,121 @@
+use crate::data_models::clean_models::untis::{Entity, WeekTimeTable};
+use crate::errors::ApiError;
+use crate::persistence_manager::{FeedSettings, PersistenceManager};
+use crate::timetable_store::TimetableStore;
+use crate::request_proxy::try_invoke;
+use crate::storage_backend::is_tauri;
+use crate::untis::ics_export::{school_timezone, to_ics};
+use crate::untis::teacher_table_generator::gen_all_timetables;
+use crate::untis::untis_week::Week;
+use gloo_timers::future::TimeoutFuture;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use wasm_bindgen_futures::spawn_local;
+
+// every fetched week asks for a publish, they are collected until none came for this long
+const PUBLISH_DELAY_MS: u32 = 2_000;
+
+thread_local! {
+    static PUBLISH_GENERATION: Cell<u32> = const { Cell::new(0) };
+}
+
+#[derive(Serialize)]
+struct ConfigureArgs {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Serialize)]
+struct PublishArgs {
+    feeds: HashMap<String, String>,
+}
+
+// starts, restarts or stops the localhost server in the tauri backend
+pub async fn configure_feed_server(settings: &FeedSettings) -> Result<(), ApiError> {
+    let args = serde_wasm_bindgen::to_value(&ConfigureArgs {
+        enabled: settings.enabled,
+        port: settings.port,
+    })
+    .map_err(|e| e.to_string())?;
+
+    try_invoke("configure_ics_server", args)
+        .await
+        .map_err(|e| ApiError::Miscellaneous(e.as_string().unwrap_or("Failed to start the feed server".to_string())))?;
+    Ok(())
+}
+
+// rebuilds every feed from all cached weeks and tables, served as /calendar/{category}/{name}.ics
+pub async fn publish_all_feeds() -> Result<(), ApiError> {
+    let enabled = is_tauri() && PersistenceManager::get_settings()?.is_some_and(|s| s.feed_settings.enabled);
+    if !enabled {
+        return Ok(());
+    }
+
+    let cache = TimetableStore::all_weeks().await?;
+
+    // keyed by feed path, the same room may be resolved in one week and not in another.
+    // tables are keyed by week start, a directly fetched table replaces the crawled one of the same week
+    let mut per_entity: HashMap<String, (Entity, BTreeMap<String, WeekTimeTable>)> = HashMap::new();
+    let mut add = |entity: Entity, week: &Week, table: WeekTimeTable| {
+        let (_, tables) = per_entity
+            .entry(format!("{}/{}", entity.category(), entity.name()))
+            .or_insert_with(|| (entity, BTreeMap::new()));
+        tables.insert(week.start.clone(), table);
+    };
+    for (week, cached) in &cache.tables {
+        let (all, _) = gen_all_timetables(cached.tables.0.clone(), cached.tables.1)?;
+        for (entity, table) in all {
+            add(entity, week, table);
+        }
+    }
+    for (week, cached) in TimetableStore::all_tables().await? {
+        add(cached.resource.entity, &week, cached.table);
+    }
+
+    let tz = school_timezone();
+    let feeds = per_entity
+        .into_iter()
+        .map(|(path, (entity, tables))| (path, to_ics(&entity, &tables.into_values().collect::<Vec<_>>(), tz)))
+        .collect();
+
+    // a plain object instead of a js Map, tauri can't deserialize the latter
+    let args = PublishArgs { feeds }
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| e.to_string())?;
+    try_invoke("publish_ics_feeds", args)
+        .await
+        .map_err(|e| ApiError::Miscellaneous(e.as_string().unwrap_or("Failed to publish feeds".to_string())))?;
+    Ok(())
+}
+
+// rebuilding every feed reads the whole cache, so a crawl or refresh publishes once after its last week
+pub fn schedule_publish() {
+    let generation = PUBLISH_GENERATION.with(|g| {
+        g.set(g.get().wrapping_add(1));
+        g.get()
+    });
+    spawn_local(async move {
+        TimeoutFuture::new(PUBLISH_DELAY_MS).await;
+        if PUBLISH_GENERATION.with(|g| g.get()) != generation {
+            return;
+        }
+        // a failing feed must not break the timetable itself
+        if let Err(e) = publish_all_feeds().await {
+            log::warn!("Failed to publish calendar feeds: {}", e);
+        }
+    });
+}
+
+// applies the stored settings and serves whatever is cached until the next refresh.
+// the server lives in the tauri backend, a plain browser has none
+pub async fn start_feed_server() -> Result<(), ApiError> {
+    if !is_tauri() {
+        return Ok(());
+    }
+    let Some(settings) = PersistenceManager::get_settings()? else {
+        return Ok(());
+    };
+    configure_feed_server(&settings.feed_settings).await?;
+    publish_all_feeds().await
+}
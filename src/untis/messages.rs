@@ -0,0 +1,191 @@
+use crate::data_models::clean_models::messages::*;
+use crate::data_models::response_models::untis_messages::*;
+use crate::errors::ApiError;
+use crate::persistence_manager::PersistenceManager;
+use crate::untis::auth::AuthHelper;
+use std::collections::HashMap;
+
+pub struct MessagesClient {
+    school_name: String,
+}
+
+impl MessagesClient {
+    pub fn new() -> Result<Self, ApiError> {
+        let school_name = PersistenceManager::get_settings()?
+            .ok_or(ApiError::Authentication("Settings are empty".to_string()))?
+            .untis_auth
+            .school_identifier;
+
+        Ok(Self { school_name })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/messages{}",
+            self.school_name, path
+        )
+    }
+
+    async fn get(&self, path: &str) -> Result<String, ApiError> {
+        let response = AuthHelper::authorized_request("GET", &self.url(path), HashMap::new(), "".to_string()).await?;
+        Ok(response.body)
+    }
+
+    pub async fn get_mailbox(&self) -> Result<Mailbox, ApiError> {
+        let inbox = self.get("").await?;
+        let sent = self.get("/sent").await?;
+        let (inbox, read_confirmations) = parse_inbox(&inbox)?;
+
+        Ok(Mailbox {
+            inbox,
+            sent: parse_sent(&sent)?,
+            read_confirmations,
+        })
+    }
+
+    pub async fn get_message(&self, id: i64) -> Result<MessageDetail, ApiError> {
+        let body = self.get(&format!("/{}", id)).await?;
+        parse_message_detail(&body)
+    }
+}
+
+// the parsers are kept free of any network access so they can be fed recorded responses
+
+pub fn parse_inbox(body: &str) -> Result<(Vec<Message>, Vec<Message>), ApiError> {
+    let response: UntisInboxResponse = serde_json::from_str(body)
+        .map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+    let inbox = response
+        .incoming_messages
+        .into_iter()
+        .map(|m| Message::from((m, MessageFolder::Inbox)))
+        .collect();
+    let read_confirmations = response
+        .read_confirmation_messages
+        .into_iter()
+        .map(|m| Message::from((m, MessageFolder::ReadConfirmations)))
+        .collect();
+
+    Ok((inbox, read_confirmations))
+}
+
+pub fn parse_sent(body: &str) -> Result<Vec<Message>, ApiError> {
+    let response: UntisSentResponse = serde_json::from_str(body)
+        .map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+    Ok(response
+        .sent_messages
+        .into_iter()
+        .map(|m| Message::from((m, MessageFolder::Sent)))
+        .collect())
+}
+
+pub fn parse_message_detail(body: &str) -> Result<MessageDetail, ApiError> {
+    let response: UntisMessageDetail = serde_json::from_str(body)
+        .map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+    Ok(MessageDetail::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INBOX: &str = include_str!("fixtures/messages_inbox.json");
+    const SENT: &str = include_str!("fixtures/messages_sent.json");
+    const DETAIL: &str = include_str!("fixtures/messages_detail.json");
+
+    fn mailbox() -> Mailbox {
+        let (inbox, read_confirmations) = parse_inbox(INBOX).unwrap();
+        Mailbox {
+            inbox,
+            sent: parse_sent(SENT).unwrap(),
+            read_confirmations,
+        }
+    }
+
+    #[test]
+    fn parses_the_inbox() {
+        let (inbox, read_confirmations) = parse_inbox(INBOX).unwrap();
+        assert_eq!(inbox.len(), 4);
+
+        let first = &inbox[0];
+        assert_eq!(first.id, 4711);
+        assert_eq!(first.folder, MessageFolder::Inbox);
+        assert_eq!(first.subject, "Elternsprechtag");
+        assert_eq!(first.preview, "Liebe Eltern,\nder Elternsprechtag findet am 8. Oktober statt.");
+        assert_eq!(first.sender.as_ref().map(|s| s.display_name.as_str()), Some("Huber Anna"));
+        assert!(!first.is_read && first.has_attachments && !first.is_reply);
+        assert!(inbox[2].is_reply);
+        assert!(inbox[0].sent < inbox[2].sent);
+
+        assert_eq!(read_confirmations.len(), 1);
+        assert_eq!(read_confirmations[0].id, 4690);
+        assert_eq!(read_confirmations[0].folder, MessageFolder::ReadConfirmations);
+    }
+
+    #[test]
+    fn parses_sent_messages() {
+        let sent = parse_sent(SENT).unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].folder, MessageFolder::Sent);
+        // there is no read flag for sent messages
+        assert!(sent[0].is_read);
+        assert_eq!(sent[0].preview, "Sehr geehrter Herr Berger,\nich war gestern krank.");
+        assert_eq!(sent[0].sender.as_ref().and_then(|s| s.class_name.as_deref()), Some("3AHIT"));
+        assert_eq!(
+            sent[0].recipients,
+            vec![MessagePerson { id: Some(131), display_name: "Berger Thomas".to_string(), class_name: None }]
+        );
+    }
+
+    #[test]
+    fn parses_a_message_with_its_history() {
+        let detail = parse_message_detail(DETAIL).unwrap();
+        assert_eq!(detail.id, 4720);
+        assert_eq!(detail.content, "Hallo Max,\n\ndanke für die Info.\nDie Hausübung & die Unterlagen findest du im Kurs.");
+
+        let attachments: Vec<(&str, &str)> = detail.attachments.iter().map(|a| (a.id.as_str(), a.name.as_str())).collect();
+        assert_eq!(
+            attachments,
+            vec![("b7e3c0d2-5f4a-4a8e-9a51-0d2f6c1e8a40", "Arbeitsblatt.pdf"), ("17", "Tafelbild.png")]
+        );
+        assert_eq!(detail.attachments[1].url, "");
+
+        assert_eq!(detail.history.len(), 1);
+        assert_eq!(detail.history[0].id, 4718);
+        assert!(detail.history[0].sent < detail.sent);
+        assert_eq!(detail.confirmation, Some(ReadConfirmation { confirmed_at: None, can_confirm: true }));
+    }
+
+    #[test]
+    fn rejects_anything_but_json() {
+        let login_page = "<!DOCTYPE html><html><head><title>WebUntis</title></head><body>Login</body></html>";
+        assert!(matches!(parse_inbox(login_page), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_sent(login_page), Err(ApiError::Parsing(_))));
+        assert!(matches!(parse_message_detail(login_page), Err(ApiError::Parsing(_))));
+    }
+
+    #[test]
+    fn threads_replies_by_topic_and_participants() {
+        let threads = mailbox().threads(MessageFolder::Inbox);
+        let ids: Vec<Vec<i64>> = threads.iter().map(|t| t.messages.iter().map(|m| m.id).collect()).collect();
+        // the same subject from another teacher is another conversation
+        assert_eq!(ids, vec![vec![4720, 4731], vec![4725], vec![4711]]);
+        assert_eq!(threads[0].topic, "Fehlstunde Mathematik");
+        assert_eq!(threads[0].id, 4720);
+        assert_eq!(threads[0].latest().map(|m| m.id), Some(4731));
+    }
+
+    #[test]
+    fn starts_a_new_thread_for_a_new_message_on_the_same_topic() {
+        let mut mailbox = mailbox();
+        let mut again = mailbox.sent[0].clone();
+        again.id = 4800;
+        again.sent += chrono::Duration::days(7);
+        mailbox.sent.push(again);
+
+        let threads = mailbox.threads(MessageFolder::Sent);
+        assert_eq!(threads.iter().map(|t| t.id).collect::<Vec<_>>(), vec![4800, 4718]);
+    }
+}
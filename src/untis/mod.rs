@@ -1,5 +1,13 @@
 pub mod untis_client;
 pub mod untis_week;
 pub mod cached_untis_client;
+pub mod messages;
+pub mod absences;
+pub mod ics_export;
+pub mod ics_feed;
+pub mod timetable_diff;
+pub mod background_refresh;
+pub mod setschool_uri;
 mod auth;
-mod teacher_table_generator;
\ No newline at end of file
+mod request_scheduler;
+pub mod teacher_table_generator;
\ No newline at end of file
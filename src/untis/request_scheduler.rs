@@ -0,0 +1,143 @@
+use crate::errors::ApiError;
+use crate::persistence_manager::PersistenceManager;
+use crate::request_proxy::ProxyResponse;
+use futures::channel::oneshot;
+use futures::future::{select, Either};
+use gloo_timers::future::TimeoutFuture;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::future::Future;
+use wasm_bindgen_futures::spawn_local;
+
+const REQUEST_TIMEOUT_MS: u32 = 20_000;
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF_MS: u32 = 500; // doubled with every retry
+const MAX_BACKOFF_MS: u32 = 10_000;
+
+thread_local! {
+    static IN_FLIGHT: Cell<usize> = const { Cell::new(0) };
+    // requests waiting for a free slot, served first come first served
+    static WAITING: RefCell<VecDeque<oneshot::Sender<Permit>>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// a slot of the in-flight limit, freed or handed to the next waiting request when dropped
+struct Permit;
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        loop {
+            let Some(next) = WAITING.with(|w| w.borrow_mut().pop_front()) else {
+                IN_FLIGHT.with(|n| n.set(n.get().saturating_sub(1)));
+                return;
+            };
+            // a waiter that gave up returns the permit, which must not free the slot a second time
+            match next.send(Permit) {
+                Ok(()) => return,
+                Err(permit) => std::mem::forget(permit),
+            }
+        }
+    }
+}
+
+// keeps webuntis from throttling us: a bounded number of requests in flight,
+// retries with exponential backoff and a timeout per attempt
+pub struct RequestScheduler;
+
+impl RequestScheduler {
+    pub async fn send<F, Fut>(request: F) -> Result<ProxyResponse, ApiError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<ProxyResponse, ApiError>> + 'static,
+    {
+        let mut attempt = 0;
+        loop {
+            let permit = Self::acquire().await;
+            let result = Self::with_timeout(request(), permit).await;
+
+            let retry_after = match &result {
+                Ok(response) if response.status == 429 || response.status >= 500 => retry_after_ms(response),
+                Err(ApiError::Network(_)) => None,
+                _ => return result,
+            };
+
+            if attempt >= MAX_RETRIES {
+                return match result {
+                    Ok(response) => Err(ApiError::Network(format!("Untis answered with status {}", response.status))),
+                    err => err,
+                };
+            }
+
+            let backoff = BASE_BACKOFF_MS.saturating_mul(1 << attempt).min(MAX_BACKOFF_MS);
+            // some jitter, so throttled requests don't all come back at the same moment
+            let jitter = (js_sys::Math::random() * backoff as f64 / 2.0) as u32;
+            let delay = retry_after.unwrap_or(backoff + jitter);
+            log::info!("Retrying an Untis request in {} ms (attempt {})", delay, attempt + 1);
+            TimeoutFuture::new(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn acquire() -> Permit {
+        let max = max_in_flight();
+        let free = IN_FLIGHT.with(|n| {
+            let free = n.get() < max;
+            if free {
+                n.set(n.get() + 1);
+            }
+            free
+        });
+        if free {
+            return Permit;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        WAITING.with(|w| w.borrow_mut().push_back(tx));
+        match rx.await {
+            Ok(permit) => permit,
+            // not expected, the waiting senders are only ever dropped after sending
+            Err(_) => {
+                IN_FLIGHT.with(|n| n.set(n.get() + 1));
+                Permit
+            }
+        }
+    }
+
+    // a timed out request can't be aborted, it keeps its permit until it really finished so it still counts
+    async fn with_timeout(
+        request: impl Future<Output = Result<ProxyResponse, ApiError>> + 'static,
+        permit: Permit,
+    ) -> Result<ProxyResponse, ApiError> {
+        let (tx, rx) = oneshot::channel();
+        spawn_local(async move {
+            let result = request.await;
+            drop(permit);
+            let _ = tx.send(result);
+        });
+
+        match select(rx, TimeoutFuture::new(REQUEST_TIMEOUT_MS)).await {
+            Either::Left((Ok(result), _)) => result,
+            Either::Left((Err(_), _)) => Err(ApiError::Network("The Untis request was dropped".to_string())),
+            Either::Right(_) => Err(ApiError::Network(format!("No answer from Untis after {} seconds", REQUEST_TIMEOUT_MS / 1000))),
+        }
+    }
+}
+
+fn max_in_flight() -> usize {
+    PersistenceManager::get_settings()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .timetable_settings
+        .max_parallel_requests
+        .max(1) as usize
+}
+
+// 429 answers may say how long to wait, only the seconds form is supported
+fn retry_after_ms(response: &ProxyResponse) -> Option<u32> {
+    response
+        .headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("retry-after"))
+        .and_then(|(_, v)| v.first()?.trim().parse::<u32>().ok())
+        .map(|seconds| seconds.saturating_mul(1000).min(MAX_BACKOFF_MS))
+}
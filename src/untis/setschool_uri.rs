@@ -0,0 +1,64 @@
+use totp_rs::Secret;
+
+const SCHEME: &str = "untis://";
+const SETSCHOOL: &str = "setschool";
+
+// the content of the "mobile access" qr code shown in the webuntis profile,
+// e.g. untis://setschool?url=nessa.webuntis.com&school=my-school&user=jdoe&key=ABCDEFGHIJKLMNOP
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetSchoolUri {
+    pub school: String,
+    pub user: String,
+    pub key: String, // base32 totp secret
+}
+
+impl SetSchoolUri {
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let uri = uri.trim();
+        let rest = uri
+            .get(..SCHEME.len())
+            .filter(|s| s.eq_ignore_ascii_case(SCHEME))
+            .map(|_| &uri[SCHEME.len()..])
+            .ok_or("This is not a WebUntis link, it has to start with untis://setschool")?;
+
+        let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+        if !action.trim_end_matches('/').eq_ignore_ascii_case(SETSCHOOL) {
+            return Err(format!("Unsupported WebUntis link \"{}\", expected untis://setschool", action));
+        }
+
+        let params: Vec<(String, String)> =
+            serde_urlencoded::from_str(query).map_err(|_| "The link's parameters can't be read".to_string())?;
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .ok_or(format!("The link has no \"{}\"", name))
+        };
+
+        // only checked, requests always go to the school's own subdomain
+        let server = param("url")?;
+        let host = server
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if !host.ends_with(".webuntis.com") {
+            return Err(format!("\"{}\" is not a WebUntis server", server));
+        }
+
+        let key = param("key")?.to_uppercase().replace(' ', "");
+        if Secret::Encoded(key.clone()).to_bytes().is_err() {
+            return Err("The key is not a valid Base32 secret".to_string());
+        }
+
+        Ok(Self {
+            school: param("school")?,
+            user: param("user")?,
+            key,
+        })
+    }
+}
@@ -1,8 +1,12 @@
 use crate::data_models::clean_models::untis::{
-    ChangeStatus, Class, DayTimeTable, Entity, LessonBlock, WeekTimeTable,
+    ChangeStatus, Class, DayNote, DayNoteKind, DayTimeTable, Entity, LessonBlock, MasterData, WeekTimeTable,
 };
 use crate::errors::ApiError;
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// a derived teacher or room table, its lessons per day
+type DerivedTable = (Entity, HashMap<NaiveDate, Vec<LessonBlock>>);
 
 pub fn gen_all_timetables(
     class_results: HashMap<Class, WeekTimeTable>,
@@ -13,8 +17,10 @@ pub fn gen_all_timetables(
         .map(|(class, table)| (Entity::Class(class), table))
         .collect();
 
-    let mut entity_lesson_map: HashMap<Entity, HashMap<chrono::NaiveDate, Vec<LessonBlock>>> =
-        HashMap::new();
+    // matched by name, a room may appear resolved in one class and only named in another
+    let mut entity_lesson_map: HashMap<(&'static str, String), DerivedTable> = HashMap::new();
+    // every derived table gets all school days, so holidays show up for teachers and rooms too
+    let days = school_days(all_timetables.values());
 
     for table in all_timetables.values() {
         for day_table in table.days.iter() {
@@ -24,27 +30,30 @@ pub fn gen_all_timetables(
                         continue;
                     }
 
-                    match &entity_wrapper.inner {
-                        Entity::Teacher(_) | Entity::Room(_) => {
-                            entity_lesson_map
-                                .entry(entity_wrapper.inner.clone())
-                                .or_default()
-                                .entry(day_table.date)
-                                .or_default()
-                                .push(lesson.clone());
-                        }
-                        _ => {}
+                    let inner = &entity_wrapper.inner;
+                    if !matches!(inner, Entity::Teacher(_) | Entity::Room(_)) {
+                        continue;
                     }
+
+                    let (entity, lessons_by_day) = entity_lesson_map
+                        .entry((inner.category(), inner.name()))
+                        .or_insert_with(|| (inner.clone(), HashMap::new()));
+                    if is_resolved(inner) && !is_resolved(entity) {
+                        *entity = inner.clone();
+                    }
+                    lessons_by_day.entry(day_table.date).or_default().push(lesson.clone());
                 }
             }
         }
     }
 
-    for (entity, lessons) in entity_lesson_map {
-        let mut new_table = WeekTimeTable { days: vec![] };
-
-        for (date, lessons) in lessons {
-            new_table.days.push(DayTimeTable { date, lessons })
+    for (entity, mut lessons) in entity_lesson_map.into_values() {
+        let mut new_table = WeekTimeTable {
+            days: days.clone(),
+            ..Default::default()
+        };
+        for day in &mut new_table.days {
+            day.lessons = lessons.remove(&day.date).unwrap_or_default();
         }
 
         all_timetables.insert(entity, new_table);
@@ -52,3 +61,56 @@ pub fn gen_all_timetables(
 
     Ok((all_timetables, pre_selected))
 }
+
+// teachers and rooms without a single lesson this week still get a timetable, so free rooms can be found
+pub fn add_free_entities(all_timetables: &mut HashMap<Entity, WeekTimeTable>, master: &MasterData) {
+    let classes = all_timetables.iter().filter(|(e, _)| matches!(e, Entity::Class(_))).map(|(_, t)| t);
+    let days = school_days(classes);
+    if days.is_empty() {
+        return;
+    }
+
+    let known: HashSet<_> = all_timetables.keys().map(|e| (e.category(), e.name())).collect();
+    let free = master
+        .teachers
+        .values()
+        .cloned()
+        .map(Entity::Teacher)
+        .chain(master.rooms.values().cloned().map(Entity::Room));
+    for entity in free.filter(|e| !known.contains(&(e.category(), e.name()))) {
+        all_timetables.insert(entity, WeekTimeTable {
+            days: days.clone(),
+            ..Default::default()
+        });
+    }
+}
+
+// the days of the week any class has, without lessons but with the holidays, which apply to the whole school
+fn school_days<'a>(tables: impl Iterator<Item = &'a WeekTimeTable>) -> Vec<DayTimeTable> {
+    let mut days: BTreeMap<NaiveDate, Vec<DayNote>> = BTreeMap::new();
+    for day_table in tables.flat_map(|t| &t.days) {
+        let holidays = days.entry(day_table.date).or_default();
+        for note in day_table.notes.iter().filter(|n| n.kind == DayNoteKind::Holiday) {
+            if !holidays.iter().any(|h| h.title == note.title) {
+                holidays.push(note.clone());
+            }
+        }
+    }
+
+    days.into_iter()
+        .map(|(date, notes)| DayTimeTable {
+            date,
+            lessons: vec![],
+            notes,
+        })
+        .collect()
+}
+
+// whether the entity carries its master data and not just the name from a lesson
+fn is_resolved(entity: &Entity) -> bool {
+    match entity {
+        Entity::Teacher(t) => t.id.is_some(),
+        Entity::Room(r) => r.id.is_some(),
+        _ => true,
+    }
+}
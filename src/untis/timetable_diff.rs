@@ -0,0 +1,119 @@
+use crate::data_models::clean_models::changes::{ChangeKind, TimetableChange};
+use crate::data_models::clean_models::untis::{ChangeStatus, Entity, LessonBlock, WeekTimeTable};
+use crate::errors::ApiError;
+use crate::persistence_manager::TimeTables;
+use crate::untis::teacher_table_generator::gen_all_timetables;
+use chrono::Local;
+use std::collections::HashMap;
+
+// compares two fetches of the same week for every class, teacher and room
+pub fn diff_timetables(old: &TimeTables, new: &TimeTables) -> Result<Vec<TimetableChange>, ApiError> {
+    let (old_tables, _) = gen_all_timetables(old.0.clone(), old.1)?;
+    let (new_tables, _) = gen_all_timetables(new.0.clone(), new.1)?;
+
+    let mut changes = Vec::new();
+    // entities missing on either side were not fetched, which says nothing about their lessons
+    for (entity, new_table) in &new_tables {
+        if let Some(old_table) = old_tables.get(entity) {
+            changes.extend(diff_week(entity, old_table, new_table));
+        }
+    }
+
+    changes.sort_by_key(|c| c.time_range.start);
+    Ok(changes)
+}
+
+// compares two fetches of a single timetable
+pub fn diff_table(entity: &Entity, old: &WeekTimeTable, new: &WeekTimeTable) -> Vec<TimetableChange> {
+    let mut changes = diff_week(entity, old, new);
+    changes.sort_by_key(|c| c.time_range.start);
+    changes
+}
+
+fn diff_week(entity: &Entity, old: &WeekTimeTable, new: &WeekTimeTable) -> Vec<TimetableChange> {
+    let detected = Local::now().naive_local();
+    let change = |lesson: &LessonBlock, kind| TimetableChange {
+        entity: entity.clone(),
+        subject: subject(lesson),
+        time_range: lesson.time_range.clone(),
+        kind,
+        detected,
+    };
+
+    let old_lessons = by_ids(old);
+    let new_lessons = by_ids(new);
+    let mut changes = Vec::new();
+
+    for (ids, lesson) in &new_lessons {
+        let Some(previous) = old_lessons.get(ids) else {
+            changes.push(change(lesson, ChangeKind::Added));
+            continue;
+        };
+
+        if lesson.status == "CANCELLED" && previous.status != "CANCELLED" {
+            changes.push(change(lesson, ChangeKind::Cancelled));
+            continue;
+        }
+        if lesson.time_range != previous.time_range {
+            changes.push(change(lesson, ChangeKind::Moved { from: previous.time_range.clone() }));
+        }
+
+        let (from, to) = (names(previous, is_teacher), names(lesson, is_teacher));
+        if from != to {
+            changes.push(change(lesson, ChangeKind::TeacherChanged { from, to }));
+        }
+        let (from, to) = (names(previous, is_room), names(lesson, is_room));
+        if from != to {
+            changes.push(change(lesson, ChangeKind::RoomChanged { from, to }));
+        }
+    }
+
+    for (ids, lesson) in &old_lessons {
+        if !new_lessons.contains_key(ids) {
+            changes.push(change(lesson, ChangeKind::Removed));
+        }
+    }
+
+    changes
+}
+
+// lessons are matched by their untis ids, blocks without ids (like standalone exams) are skipped
+fn by_ids(table: &WeekTimeTable) -> HashMap<&[i64], &LessonBlock> {
+    table
+        .days
+        .iter()
+        .flat_map(|d| &d.lessons)
+        .filter(|l| !l.ids.is_empty())
+        .map(|l| (l.ids.as_slice(), l))
+        .collect()
+}
+
+fn names(lesson: &LessonBlock, filter: fn(&Entity) -> bool) -> Vec<String> {
+    let mut names: Vec<String> = lesson
+        .entities
+        .iter()
+        .filter(|e| e.status != ChangeStatus::Removed && filter(&e.inner))
+        .map(|e| e.inner.name())
+        .collect();
+    names.sort();
+    names
+}
+
+fn is_teacher(entity: &Entity) -> bool {
+    matches!(entity, Entity::Teacher(_))
+}
+
+fn is_room(entity: &Entity) -> bool {
+    matches!(entity, Entity::Room(_))
+}
+
+fn subject(lesson: &LessonBlock) -> String {
+    lesson
+        .entities
+        .iter()
+        .find_map(|e| match &e.inner {
+            Entity::Subject(s) => Some(s.short_name.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| lesson.r#type.clone())
+}
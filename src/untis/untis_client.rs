@@ -1,11 +1,26 @@
 use crate::data_models::clean_models::untis::*;
+use crate::data_models::response_models::untis_exams::UntisExamsResponse;
+use crate::data_models::response_models::untis_homework::UntisHomeworkResponse;
 use crate::data_models::response_models::untis_response_models::*;
 use crate::errors::ApiError;
-use crate::persistence_manager::PersistenceManager;
+use crate::persistence_manager::{PersistenceManager, UntisAuthMode};
 use crate::untis::auth::AuthHelper;
 use crate::untis::untis_week::Week;
-use futures::future::join_all;
+use chrono::{Duration, NaiveDate};
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use std::collections::HashMap;
+use yew::Callback;
+
+// reported for every class the crawl finishes, the table is none when fetching it failed
+#[derive(Clone, PartialEq, Debug)]
+pub struct CrawlProgress {
+    pub class: Class,
+    pub table: Option<WeekTimeTable>,
+    pub loaded: usize,
+    pub total: usize,
+    pub own_class: Option<i32>,
+}
 
 pub struct UntisClient {
     school_name: String,
@@ -25,21 +40,19 @@ impl UntisClient {
         AuthHelper::is_authenticated()
     }
 
-    pub async fn authenticate(school_name: String, username: String, secret: String) -> Result<(), ApiError> {
-        AuthHelper::authenticate(school_name, username, secret).await
+    pub async fn authenticate(school_name: String, username: String, secret: String, mode: UntisAuthMode) -> Result<(), ApiError> {
+        AuthHelper::authenticate(school_name, username, secret, mode).await
     }
 
-    async fn get_classes(&self, week: Week) -> Result<(Vec<Class>, Option<i32>), ApiError> {
-        let url = format!(
-            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/timetable/filter?resourceType=CLASS&timetableType=STANDARD&start={}&end={}",
-            self.school_name,
-            week.start,
-            week.end,
-        );
+    pub async fn get_classes(&self, week: Week) -> Result<(Vec<Class>, Option<i32>), ApiError> {
+        let (classes, pre_selected, _) = self.get_class_filter(week).await?;
+        Ok((classes, pre_selected))
+    }
 
-        let response = AuthHelper::authorized_request("GET", url.as_str(), HashMap::new(), "".to_string()).await?;
-        let untis_data: UntisResponse =
-            serde_json::from_str(&response.body).map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+    // the classes with the pre-selection, and the teachers, rooms and subjects the same response lists
+    pub async fn get_class_filter(&self, week: Week) -> Result<(Vec<Class>, Option<i32>, MasterData), ApiError> {
+        let untis_data = self.get_filter(ResourceType::Class, &week).await?;
+        let master = MasterData::from(&untis_data);
 
         let classes: Vec<Class> = untis_data
             .classes
@@ -48,16 +61,137 @@ impl UntisClient {
             .map(Class::from)
             .collect();
 
-        Ok((classes, untis_data.pre_selected.map(|x| x.id)))
+        Ok((classes, untis_data.pre_selected.map(|x| x.id), master))
+    }
+
+    // teachers, rooms or the student themself the account may open timetables of, classes come with the pre-selection from get_classes
+    pub async fn get_resources(&self, kind: ResourceType, week: Week) -> Result<Vec<Resource>, ApiError> {
+        if kind == ResourceType::Class {
+            let (classes, _) = self.get_classes(week).await?;
+            return Ok(classes.into_iter().map(Resource::from).collect());
+        }
+
+        let untis_data = self.get_filter(kind, &week).await?;
+
+        // a student only ever gets their own timetable, which webuntis pre-selects. none for other accounts
+        if kind == ResourceType::Student {
+            let Some(student) = untis_data.pre_selected else {
+                return Ok(vec![]);
+            };
+            return Ok(vec![Resource {
+                kind,
+                id: student.id,
+                entity: Entity::Student(Student {
+                    id: student.id,
+                    name: student.display_name,
+                }),
+            }]);
+        }
+
+        let resources = match kind {
+            ResourceType::Teacher => untis_data
+                .teachers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| Resource {
+                    kind,
+                    id: t.teacher.id,
+                    entity: Entity::Teacher(Teacher::from(t)),
+                })
+                .collect(),
+            _ => untis_data
+                .rooms
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| Resource {
+                    kind,
+                    id: r.room.id,
+                    entity: Entity::Room(Room::from(r)),
+                })
+                .collect(),
+        };
+        Ok(resources)
+    }
+
+    // what the account may open timetables of, together with the school's teachers, rooms and subjects
+    async fn get_filter(&self, kind: ResourceType, week: &Week) -> Result<UntisResponse, ApiError> {
+        let url = format!(
+            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/timetable/filter?resourceType={}&timetableType=STANDARD&start={}&end={}",
+            self.school_name,
+            kind.as_param(),
+            week.start,
+            week.end,
+        );
+
+        let response = AuthHelper::authorized_request("GET", url.as_str(), HashMap::new(), "".to_string()).await?;
+        serde_json::from_str(&response.body).map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))
+    }
+
+    // a single timetable straight from webuntis, with exams and, for the user's own class, homework attached
+    pub async fn get_resource_timetable(&self, week: Week, resource: &Resource, own_class: Option<i32>) -> Result<WeekTimeTable, ApiError> {
+        let mut table = match &resource.entity {
+            Entity::Class(class) => self.get_timetable(week.clone(), class.clone()).await?,
+            _ => WeekTimeTable {
+                days: self.get_days(week.clone(), resource.kind, resource.id).await?,
+                ..Default::default()
+            },
+        };
+
+        let name = resource.entity.name();
+        if let Ok(exams) = self.get_exams(week.start_date(), week.end_date()).await {
+            let exams = match resource.kind {
+                // exams of the student's classes, in subjects they actually take
+                ResourceType::Student => {
+                    let lessons: Vec<_> = table.days.iter().flat_map(|d| &d.lessons).collect();
+                    let classes: Vec<_> = lessons
+                        .iter()
+                        .flat_map(|l| &l.entities)
+                        .filter(|e| matches!(e.inner, Entity::Class(_)))
+                        .map(|e| e.inner.name())
+                        .collect();
+                    exams
+                        .into_iter()
+                        .filter(|e| classes.iter().any(|c| e.classes.contains(c)) && lessons.iter().any(|l| l.teaches(&e.subject)))
+                        .collect()
+                }
+                _ => exams.into_iter().filter(|e| e.concerns(&name)).collect(),
+            };
+            table.attach_exams(exams);
+        }
+
+        let own = match resource.kind {
+            ResourceType::Student => true,
+            ResourceType::Class => Some(resource.id) == own_class,
+            _ => false,
+        };
+        if own && let Ok(homework) = self.get_homework(week).await {
+            table.attach_homework(homework);
+        }
+        Ok(table)
     }
 
     pub async fn get_timetable(&self, week: Week, class: Class) -> Result<WeekTimeTable, ApiError> {
+        let mut days = self.get_days(week, ResourceType::Class, class.id).await?;
+        for day_table in &mut days {
+            for lesson in &mut day_table.lessons {
+                lesson.entities.push(Tracked {
+                    inner: Entity::Class(class.clone()),
+                    status: ChangeStatus::Regular,
+                });
+            }
+        }
+
+        Ok(WeekTimeTable { days, ..Default::default() })
+    }
+
+    pub async fn get_days(&self, week: Week, kind: ResourceType, id: i32) -> Result<Vec<DayTimeTable>, ApiError> {
         let url = format!(
-            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/timetable/entries?start={}&end={}&format=1&resourceType=CLASS&resources={}&periodTypes=&timetableType=STANDARD&",
+            "https://{}.webuntis.com/WebUntis/api/rest/view/v1/timetable/entries?start={}&end={}&format=1&resourceType={}&resources={}&periodTypes=&timetableType=STANDARD&",
             self.school_name,
             week.start,
             week.end,
-            class.id,
+            kind.as_param(),
+            id,
         );
 
         let response = AuthHelper::authorized_request("GET", url.as_str(), HashMap::new(), "".to_string())
@@ -89,54 +223,123 @@ impl UntisClient {
             return Err(ApiError::Miscellaneous(format!("Error in response from Untis: {:#?}", error)));
         }
 
-        let day_tables = untis_data
+        Ok(untis_data
             .days
             .unwrap_or_default()
             .into_iter()
-            .map(|day| {
-                let mut day_table = DayTimeTable::from(day);
-                for lesson in &mut day_table.lessons {
-                    lesson.entities.push(Tracked {
-                        inner: Entity::Class(class.clone()),
-                        status: ChangeStatus::Regular,
-                    });
-                }
-                day_table
-            })
-            .collect();
+            .map(DayTimeTable::from)
+            .collect())
+    }
+
+    // homework is listed by the day it was given, so we look back far enough to catch everything due this week
+    pub async fn get_homework(&self, week: Week) -> Result<Vec<Homework>, ApiError> {
+        let url = format!(
+            "https://{}.webuntis.com/WebUntis/api/homeworks/lessons?startDate={}&endDate={}",
+            self.school_name,
+            (week.start_date() - Duration::weeks(4)).format("%Y%m%d"),
+            week.end.replace('-', ""),
+        );
+
+        let response = AuthHelper::authorized_request("GET", url.as_str(), HashMap::new(), "".to_string()).await?;
+        let untis_data: UntisHomeworkResponse =
+            serde_json::from_str(&response.body).map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+        Ok(Vec::<Homework>::from(untis_data.data))
+    }
+
+    // exams of every class the account can see
+    pub async fn get_exams(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<Exam>, ApiError> {
+        let url = format!(
+            "https://{}.webuntis.com/WebUntis/api/exams?startDate={}&endDate={}&klasseId=-1&withGrades=false",
+            self.school_name,
+            start.format("%Y%m%d"),
+            end.format("%Y%m%d"),
+        );
 
-        Ok(WeekTimeTable { days: day_tables })
+        let response = AuthHelper::authorized_request("GET", url.as_str(), HashMap::new(), "".to_string()).await?;
+        let untis_data: UntisExamsResponse =
+            serde_json::from_str(&response.body).map_err(|e| ApiError::Parsing(format!("Serialization error: {}", e)))?;
+
+        let mut exams: Vec<Exam> = untis_data.data.exams.into_iter().map(Exam::from).collect();
+        exams.sort_by_key(|e| e.time_range.start);
+        Ok(exams)
     }
 
-    async fn get_multiple_timetables(&self, week: Week, classes: &[Class]) -> Result<HashMap<Class, WeekTimeTable>, ApiError> {
-        let tasks = classes.iter().map(|class| {
+    // the scheduler limits how many of these run at once, failed classes are returned as warnings
+    async fn get_multiple_timetables(
+        &self,
+        week: Week,
+        classes: &[Class],
+        own_class: Option<i32>,
+        on_progress: Option<Callback<CrawlProgress>>,
+    ) -> (HashMap<Class, WeekTimeTable>, Vec<String>) {
+        let mut tasks: FuturesUnordered<_> = classes.iter().map(|class| {
             let week_clone = week.clone();
             let class_clone = class.clone();
             async move {
                 let result = self.get_timetable(week_clone, class_clone.clone()).await;
                 (class_clone, result)
             }
-        });
-
-        let results = join_all(tasks).await;
+        }).collect();
 
         let mut map = HashMap::new();
-        for (class, result) in results {
-            match result {
-                Ok(timetable) => { map.insert(class, timetable); }
-                Err(e) => return Err(ApiError::Miscellaneous(format!("Could not get timetable for class {}: {}", class.id, e))),
+        let mut warnings = Vec::new();
+        let mut loaded = 0;
+        // handled in the order they finish, so progress can be shown while the rest is still running
+        while let Some((class, result)) = tasks.next().await {
+            loaded += 1;
+            let table = match result {
+                Ok(timetable) => Some(timetable),
+                Err(e) => {
+                    warnings.push(format!("Could not get timetable for class {}: {}", class.name, e));
+                    None
+                }
+            };
+
+            if let Some(on_progress) = &on_progress {
+                on_progress.emit(CrawlProgress {
+                    class: class.clone(),
+                    table: table.clone(),
+                    loaded,
+                    total: classes.len(),
+                    own_class,
+                });
+            }
+            if let Some(table) = table {
+                map.insert(class, table);
             }
         }
 
-        Ok(map)
+        (map, warnings)
     }
 
+    // partial results are fine, only when no class at all could be fetched this fails
     pub async fn get_all_class_timetables(
         &self,
         week: Week,
-    ) -> Result<(HashMap<Class, WeekTimeTable>, Option<i32>), ApiError> {
+        on_progress: Option<Callback<CrawlProgress>>,
+    ) -> Result<((HashMap<Class, WeekTimeTable>, Option<i32>), Vec<String>), ApiError> {
         let (classes, pre_selected) = self.get_classes(Week::current()).await?;
-        let class_results = self.get_multiple_timetables(week.clone(), &classes).await?;
-        Ok((class_results, pre_selected))
+        let (mut class_results, warnings) = self
+            .get_multiple_timetables(week.clone(), &classes, pre_selected, on_progress)
+            .await;
+        if class_results.is_empty()
+            && let Some(first) = warnings.first() {
+            return Err(ApiError::Miscellaneous(first.clone()));
+        }
+
+        if let Ok(exams) = self.get_exams(week.start_date(), week.end_date()).await {
+            for (class, table) in class_results.iter_mut() {
+                table.attach_exams(exams.iter().filter(|e| e.classes.contains(&class.name)).cloned().collect());
+            }
+        }
+
+        // homework is only visible for the user's own class; accounts without access simply get none
+        if let Some(own) = class_results.iter_mut().find(|(c, _)| Some(c.id) == pre_selected).map(|(_, t)| t)
+            && let Ok(homework) = self.get_homework(week).await {
+            own.attach_homework(homework);
+        }
+
+        Ok(((class_results, pre_selected), warnings))
     } 
 }
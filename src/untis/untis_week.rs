@@ -37,6 +37,31 @@ impl Week {
         }
     }
 
+    pub fn covering(start: NaiveDate, end: NaiveDate) -> Vec<Self> {
+        let mut weeks = Vec::new();
+        let mut week = Self::from_date(start);
+        while week.start_date() <= end {
+            let next = week.next();
+            weeks.push(week);
+            week = next;
+        }
+        weeks
+    }
+
+    // the school year starts in september
+    pub fn school_year_start(date: NaiveDate) -> NaiveDate {
+        let year = if date.month() >= 9 { date.year() } else { date.year() - 1 };
+        NaiveDate::from_ymd_opt(year, 9, 1).unwrap_or(date)
+    }
+
+    pub fn start_date(&self) -> NaiveDate {
+        NaiveDate::parse_from_str(&self.start, "%Y-%m-%d").unwrap_or_default()
+    }
+
+    pub fn end_date(&self) -> NaiveDate {
+        NaiveDate::parse_from_str(&self.end, "%Y-%m-%d").unwrap_or_default()
+    }
+
     pub fn previous(&self) -> Self {
         self.shift_weeks(-1)
     }